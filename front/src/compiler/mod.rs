@@ -6,7 +6,8 @@ use vm::{
 };
 
 use crate::parser::ast::{
-    BinOp, Bind, Def, Expr, ExprKind, Literal, Location, MatchArm, Pattern, Stmt, StmtKind, VarDecl,
+    BinOp, Bind, Def, Expr, ExprKind, Literal, Location, MatchArm, Pattern, Rescue, Stmt, StmtKind,
+    VarDecl,
 };
 
 #[derive(Default)]
@@ -26,6 +27,7 @@ pub struct Compiler {
     scope_stack: Vec<Scope>,
     constants: Vec<Value>,
     unique_counter: usize,
+    redefine: bool,
 }
 
 impl Compiler {
@@ -33,6 +35,22 @@ impl Compiler {
         Compiler::default()
     }
 
+    /// A compiler whose globals overwrite the existing ones instead of raising, used by the REPL
+    pub fn permissive() -> Self {
+        Compiler {
+            redefine: true,
+            ..Compiler::default()
+        }
+    }
+
+    fn emit_savg(&mut self, name: VarDecl, loc: &Location) {
+        if self.redefine {
+            self.emit_op(OpCode::Redefg(name), loc);
+        } else {
+            self.emit_op(OpCode::Savg(name), loc);
+        }
+    }
+
     pub fn compile_expr(mut self, expr: &Expr) -> (Bytecode, Vec<Value>) {
         self.scope_stack.push(Scope::new());
         self.expr(expr);
@@ -108,7 +126,7 @@ impl Compiler {
         }
     }
 
-    fn if_expr(&mut self, cond: &Expr, then: &Expr, else_: &Expr, loc: &Location) {
+    fn if_expr(&mut self, cond: &Expr, then: &Expr, else_: Option<&Expr>, loc: &Location) {
         // compiles the codition
         self.expr(cond);
 
@@ -126,7 +144,11 @@ impl Compiler {
         // fix the then jump offset
         self.scope_mut().opcodes[then_label].opcode = OpCode::Jmf(self.scope().opcodes.len());
 
-        self.expr(else_);
+        // an `if` without `else` evaluates to nil when the condition is false
+        match else_ {
+            Some(else_) => self.expr(else_),
+            None => self.emit_lit(&Literal::Unit, loc),
+        }
 
         // fix the else jump offset
         self.scope_mut().opcodes[else_label].opcode = OpCode::Jmp(self.scope().opcodes.len());
@@ -191,7 +213,7 @@ impl Compiler {
 
             Pattern::Id(id) if id.as_str() != "_" => {
                 if global {
-                    self.emit_op(OpCode::Savg(*id), loc);
+                    self.emit_savg(*id, loc);
                 } else {
                     self.emit_save(*id, loc);
                 }
@@ -306,6 +328,39 @@ impl Compiler {
                 (declarations, labels)
             }
 
+            Pattern::Struct(fields) => {
+                let local = self.emit_unique(loc);
+                let mut labels = vec![];
+                let mut declarations = vec![];
+
+                // checks if the value is a struct, so `%{}` doesn't match anything else
+                self.emit_load(&local, loc);
+                self.emit_op(OpCode::Type, loc);
+                self.emit_op(OpCode::Loag("Struct".into()), loc);
+                self.emit_op(OpCode::Eq, loc);
+
+                labels.push(self.scope().opcodes.len());
+                self.emit_op(OpCode::Jmf(0), loc);
+
+                // fields that aren't in the pattern are ignored
+                for (name, field) in fields {
+                    self.emit_load(&local, loc);
+                    self.emit_op(OpCode::Has(*name), loc);
+
+                    labels.push(self.scope().opcodes.len());
+                    self.emit_op(OpCode::Jmf(0), loc);
+
+                    self.emit_load(&local, loc);
+                    self.emit_op(OpCode::Get(*name), loc);
+
+                    let (decls, offsets) = self.match_pattern(field, global, loc);
+                    labels.extend(offsets);
+                    declarations.extend(decls);
+                }
+
+                (declarations, labels)
+            }
+
             Pattern::EmptyList => {
                 self.emit_const(List::new().into(), loc);
                 self.emit_op(OpCode::Eq, loc);
@@ -323,6 +378,26 @@ impl Compiler {
         // compiles the condition
         self.expr(cond);
 
+        let (_, jmps) = self.match_arms(arms, loc);
+
+        // this section is ignored by any arm, since they jump to the next section in the end,
+        // thus, if anything here is executed, it's a sign that the match couldn't match anything,
+        // which is probably a bug, so it will just raise a new exception
+        self.emit_const("Couldn't match any clause".to_string().into(), loc);
+        self.emit_const(Symbol::from("MatchError").into(), loc);
+        self.emit_op(OpCode::Loag("raise".into()), loc);
+        self.emit_op(OpCode::Call(2), loc);
+
+        // fix all the jump offsets
+        let ip = self.scope().opcodes.len();
+        for jmp in jmps {
+            self.scope_mut().opcodes[jmp].opcode = OpCode::Jmp(ip);
+        }
+    }
+
+    /// Matches the arms against the value on the top of the stack, returns the local it's saved to
+    /// and the jumps that leave the match, to be patched once its end is known
+    fn match_arms(&mut self, arms: &[MatchArm], loc: &Location) -> (Symbol, Vec<usize>) {
         let local = self.emit_unique(loc);
 
         // keep track of all the jump offsets
@@ -337,22 +412,105 @@ impl Compiler {
             jmps.push(jmp);
         }
 
-        // this section is ignored by any arm, since they jump to the next section in the end,
-        // thus, if anything here is executed, it's a sign that the match couldn't match anything,
-        // which is probably a bug, so it will just raise a new exception
-        self.emit_const("Couldn't match any clause".to_string().into(), loc);
-        self.emit_const(Symbol::from("MatchError").into(), loc);
-        self.emit_op(OpCode::Loag("raise".into()), loc);
-        self.emit_op(OpCode::Call(2), loc);
+        (local, jmps)
+    }
 
-        // fix all the jump offsets
-        let ip = self.scope().opcodes.len();
-        for jmp in jmps {
-            self.scope_mut().opcodes[jmp].opcode = OpCode::Jmp(ip);
+    /// Turns the caught error and the thrown flag the vm pushes into what rescue binds and arms
+    /// see, the symbol of raised errors or the payload of thrown ones
+    fn error_subject(&mut self, loc: &Location) {
+        let raised_label = self.scope().opcodes.len();
+        self.emit_op(OpCode::Jmf(0), loc);
+
+        let end_label = self.scope().opcodes.len();
+        self.emit_op(OpCode::Jmp(0), loc);
+
+        self.scope_mut().opcodes[raised_label].opcode = OpCode::Jmf(self.scope().opcodes.len());
+        self.emit_op(OpCode::TupGet(0), loc);
+
+        self.scope_mut().opcodes[end_label].opcode = OpCode::Jmp(self.scope().opcodes.len());
+    }
+
+    /// Compiles a comprehension, the last generator becomes a `List.map` over the `List.filter`ed
+    /// list, and every outer one a `List.map` whose results are joined with `List.flattenDepth 1`
+    fn comprehension(
+        &mut self,
+        elem: &Expr,
+        generators: &[(Pattern, Expr)],
+        filter: Option<&Expr>,
+        bound: &[Symbol],
+        loc: &Location,
+    ) {
+        let ((pat, iter), inner) = generators.split_first().unwrap();
+
+        if inner.is_empty() {
+            self.lifted_lambda(bound, pat, loc, |this| this.expr(elem));
+            match filter {
+                Some(filter) => {
+                    self.lifted_lambda(bound, pat, loc, |this| this.expr(filter));
+                    self.expr(iter);
+                    self.call_list_method("filter", loc);
+                }
+                None => self.expr(iter),
+            }
+            self.call_list_method("map", loc);
+            return;
         }
+
+        let inner_bound = [bound, &pat.names()].concat();
+
+        self.emit_lit(&Literal::Num(1.0), loc);
+        self.lifted_lambda(bound, pat, loc, |this| {
+            this.comprehension(elem, inner, filter, &inner_bound, loc);
+        });
+        self.expr(iter);
+        self.call_list_method("map", loc);
+        self.call_list_method("flattenDepth", loc);
+    }
+
+    /// A `fn bound.. pat -> body` lambda applied to the `bound` names, so its body sees them
+    fn lifted_lambda(
+        &mut self,
+        bound: &[Symbol],
+        pat: &Pattern,
+        loc: &Location,
+        body: impl FnOnce(&mut Self),
+    ) {
+        let mut args: Vec<_> = bound.iter().map(|name| Pattern::Id(*name)).collect();
+        args.push(pat.clone());
+
+        let func = self.lambda_with(&args, loc, body);
+
+        for name in bound {
+            self.expr(&Expr::new(ExprKind::Var(*name), loc.line, loc.column));
+        }
+        if bound.len() > 1 {
+            self.emit_op(OpCode::RevN(bound.len()), loc);
+        }
+        self.emit_const(Value::Fn(func), loc);
+        if !bound.is_empty() {
+            self.emit_op(OpCode::Call(bound.len()), loc);
+        }
+    }
+
+    /// Calls a two argument `List` method on the values on top of the stack
+    fn call_list_method(&mut self, method: &str, loc: &Location) {
+        self.emit_op(OpCode::RevN(2), loc);
+        self.emit_op(OpCode::Loag("List".into()), loc);
+        self.emit_op(OpCode::Ref(method.into()), loc);
+        self.emit_op(OpCode::Call(2), loc);
     }
 
     fn lambda_expr(&mut self, args: &[Pattern], body: &Expr, loc: &Location) -> GcRef<Fn> {
+        self.lambda_with(args, loc, |this| this.expr(body))
+    }
+
+    /// Compiles a function of `args`, whose body is emitted by `body`
+    fn lambda_with(
+        &mut self,
+        args: &[Pattern],
+        loc: &Location,
+        body: impl FnOnce(&mut Self),
+    ) -> GcRef<Fn> {
         // creates the lambda scope
         self.scope_stack.push(Scope::new());
 
@@ -367,7 +525,7 @@ impl Compiler {
         }
 
         // compiles the body
-        self.expr(body);
+        body(self);
 
         // emit a jump to ignore the
         let jmp_label = self.scope().opcodes.len();
@@ -408,7 +566,7 @@ impl Compiler {
             ExprKind::Lit(lit) => self.emit_lit(lit, loc),
 
             // compiles a lambda expression
-            ExprKind::Lambda { args, body } => {
+            ExprKind::Lambda { args, body, .. } => {
                 let func = self.lambda_expr(args, body, loc);
                 self.emit_const(Value::Fn(func), loc);
             }
@@ -449,16 +607,47 @@ impl Compiler {
                 }
             }
 
-            ExprKind::If { cond, then, else_ } => self.if_expr(cond, then, else_, loc),
+            ExprKind::Global(name) => self.emit_op(OpCode::Loag(*name), loc),
+
+            ExprKind::Comprehension {
+                elem,
+                generators,
+                filter,
+            } => {
+                // lambdas don't capture, so the locals are passed to them as arguments
+                let mut bound: Vec<_> = self
+                    .scope()
+                    .locals
+                    .keys()
+                    .filter(|name| !name.as_str().starts_with('#'))
+                    .copied()
+                    .collect();
+                bound.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+                self.comprehension(elem, generators, filter.as_deref(), &bound, loc);
+            }
+
+            ExprKind::If { cond, then, else_ } => {
+                self.if_expr(cond, then, else_.as_deref(), loc);
+            }
 
             ExprKind::Match { expr, arms } => self.match_expr(expr, arms, loc),
 
-            ExprKind::Let { bind, value, body } => {
-                // compiles the value and pushes it on the stack
-                self.expr(value);
+            ExprKind::Let { binds, body } => {
+                // compiles every value before binding any name, so the bindings can't see each
+                // other
+                for (_, _, value) in binds {
+                    self.expr(value);
+                }
 
-                // try to match against the value
-                let (declarations, fix_stack) = self.match_pattern(bind, false, loc);
+                // try to match against the values, the last one is on the top of the stack
+                let mut declarations = vec![];
+                let mut fix_stack = vec![];
+                for (bind, _, _) in binds.iter().rev() {
+                    let (decls, fixes) = self.match_pattern(bind, false, loc);
+                    declarations.extend(decls);
+                    fix_stack.extend(fixes);
+                }
 
                 self.expr(body);
 
@@ -583,7 +772,7 @@ impl Compiler {
                 self.emit_op(OpCode::Ref(*method), loc);
             }
 
-            ExprKind::Try { body, bind, rescue } => {
+            ExprKind::Try { body, rescue } => {
                 // keeps track of the try location
                 let try_label = self.scope().opcodes.len();
                 self.emit_op(OpCode::Try(0), loc);
@@ -605,11 +794,82 @@ impl Compiler {
                 // pop the return from the try block
                 self.emit_op(OpCode::Pop, loc);
 
-                // saves the exception to the bind
-                self.emit_save(*bind, loc);
-
-                // compiles the rescue block
-                self.expr(rescue);
+                // the vm pushes the caught error, a `(symbol, message)` tuple or a thrown payload,
+                // and then whether it was thrown
+                match rescue {
+                    Rescue::Bind(Pattern::Id(bind), rescue) => {
+                        // saves the error symbol, or the payload, to the bind
+                        self.error_subject(loc);
+                        self.emit_save(*bind, loc);
+
+                        // compiles the rescue block
+                        self.expr(rescue);
+                    }
+                    Rescue::Bind(bind, rescue) => {
+                        // patterns match the whole error, so the flag isn't needed
+                        self.emit_op(OpCode::Pop, loc);
+                        let (declarations, fix_stack) = self.match_pattern(bind, false, loc);
+
+                        self.expr(rescue);
+
+                        for decl in declarations {
+                            self.scope_mut().locals.remove(&decl);
+                        }
+
+                        let jmp_label = self.scope().opcodes.len();
+                        self.emit_op(OpCode::Jmp(0), loc);
+
+                        for offset in fix_stack {
+                            self.scope_mut().opcodes[offset].opcode =
+                                OpCode::Jmf(self.scope().opcodes.len());
+                        }
+
+                        self.emit_const("No match of the rescued error".to_string().into(), loc);
+                        self.emit_const(Symbol::from("MatchError").into(), loc);
+                        self.emit_op(OpCode::Loag("raise".into()), loc);
+                        self.emit_op(OpCode::Call(2), loc);
+
+                        self.scope_mut().opcodes[jmp_label].opcode =
+                            OpCode::Jmp(self.scope().opcodes.len());
+                    }
+                    Rescue::Arms(arms) => {
+                        // keeps copies of the error and the flag for when no arm matches, the
+                        // pair stays on the stack to get what the arms see from
+                        self.emit_op(OpCode::Over, loc);
+                        let error = self.emit_unique(loc);
+                        self.emit_op(OpCode::Dup, loc);
+                        let thrown = self.emit_unique(loc);
+
+                        // the arms only see the symbol, or the payload
+                        self.error_subject(loc);
+                        let (_, jmps) = self.match_arms(arms, loc);
+
+                        // an error no arm matched keeps going up, thrown ones with their payload
+                        self.emit_load(&thrown, loc);
+                        let raise_label = self.scope().opcodes.len();
+                        self.emit_op(OpCode::Jmf(0), loc);
+
+                        self.emit_load(&error, loc);
+                        self.emit_op(OpCode::Loag("throw".into()), loc);
+                        self.emit_op(OpCode::Call(1), loc);
+
+                        self.scope_mut().opcodes[raise_label].opcode =
+                            OpCode::Jmf(self.scope().opcodes.len());
+
+                        // and raised ones with their message
+                        self.emit_load(&error, loc);
+                        self.emit_op(OpCode::TupGet(1), loc);
+                        self.emit_load(&error, loc);
+                        self.emit_op(OpCode::TupGet(0), loc);
+                        self.emit_op(OpCode::Loag("raise".into()), loc);
+                        self.emit_op(OpCode::Call(2), loc);
+
+                        let ip = self.scope().opcodes.len();
+                        for jmp in jmps {
+                            self.scope_mut().opcodes[jmp].opcode = OpCode::Jmp(ip);
+                        }
+                    }
+                }
 
                 // fix the end of the rescue block
                 self.scope_mut().opcodes[end_label].opcode =
@@ -623,6 +883,23 @@ impl Compiler {
 
                 self.emit_op(OpCode::Tup(xs.len()), loc);
             }
+
+            ExprKind::Struct(fields) => {
+                for (name, value) in fields.iter().rev() {
+                    self.expr(value);
+                    self.emit_const((*name).into(), loc);
+                }
+
+                self.emit_op(OpCode::Struct(fields.len()), loc);
+            }
+
+            ExprKind::StructUpdate { base, fields } => {
+                self.expr(base);
+                for (name, value) in fields {
+                    self.expr(value);
+                    self.emit_op(OpCode::Set(*name), loc);
+                }
+            }
         }
     }
 
@@ -633,11 +910,11 @@ impl Compiler {
             // compiles a `def` statement into a `Savg` instruction
             StmtKind::Def(Def { bind, value, .. }) => {
                 self.expr(value);
-                self.emit_op(OpCode::Savg(*bind), &node.location);
+                self.emit_savg(*bind, &node.location);
             }
 
             // compiles a `let` statement into a `Savg` instruction
-            StmtKind::Let { bind, value } => {
+            StmtKind::Let { bind, value, .. } => {
                 // compiles the value and pushes it on the stack
                 self.expr(value);
 
@@ -687,7 +964,7 @@ impl Compiler {
         let mut table = EnvTable::new();
         for m in members {
             let func = match &m.value.kind {
-                ExprKind::Lambda { args, body } => Value::Fn(self.lambda_expr(args, body, loc)),
+                ExprKind::Lambda { args, body, .. } => Value::Fn(self.lambda_expr(args, body, loc)),
                 _ => unreachable!(),
             };
 
@@ -739,7 +1016,7 @@ impl Compiler {
 
         self.constants[index] = Value::Module(type_);
         self.emit_op(OpCode::Push(index), loc);
-        self.emit_op(OpCode::Savg(*decl), loc);
+        self.emit_savg(*decl, loc);
     }
 
     pub fn compile_stmts(mut self, stmts: &[Stmt]) -> (Vec<OpCodeMetadata>, Vec<Value>) {
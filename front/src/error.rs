@@ -1,25 +1,107 @@
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 /// Parse errors
 pub struct ParseError {
     line: usize,
     column: usize,
-    message: &'static str,
+    kind: ParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The kind of a [`ParseError`]
+pub enum ParseErrorKind {
+    /// Found a token different from the one the parser expected
+    UnexpectedToken {
+        /// Description of what the parser expected
+        expected: String,
+        /// The token that was found instead
+        found: String,
+    },
+    /// Found a token that isn't in the set of tokens accepted at that point
+    ExpectedOneOf {
+        /// Every token the parser would have accepted
+        expected: Vec<String>,
+        /// The token that was found instead
+        found: String,
+    },
+    /// The input ended before the current construct was complete
+    UnexpectedEof,
+    /// A character that can't start any token
+    UnknownChar(char),
+    /// A string literal was never closed
+    UnclosedDelimiter,
+    /// A `/*` comment was never closed
+    UnclosedComment,
+    /// An unknown escape character inside a string literal
+    UnknownEscape(char),
+    /// A malformed `\x`, `\u` or `\U` escape sequence
+    MalformedEscape,
+    /// A number literal that couldn't be parsed
+    InvalidNumber(String),
+    /// A `_` digit separator that isn't between two digits
+    MisplacedSeparator(String),
+    /// A local name that shadows another one
+    ShadowedName(String),
+    /// `=>` used on something that isn't a function call
+    InvalidTailCall,
 }
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
+impl std::fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedToken { expected, found } => {
+                write!(f, "Expected {expected}, found '{found}'")
+            }
+            Self::ExpectedOneOf { expected, found } => {
+                write!(
+                    f,
+                    "Expected one of: {}, found '{found}'",
+                    expected.join(", ")
+                )
+            }
+            Self::UnexpectedEof => write!(f, "Unexpected end of input"),
+            Self::UnknownChar(c) => write!(f, "Unknown start of token '{c}'"),
+            Self::UnclosedDelimiter => write!(f, "Unclosed delimiter opened here"),
+            Self::UnclosedComment => write!(f, "Unclosed comment opened here"),
+            Self::UnknownEscape(c) => write!(f, "Unknown escape char '{c}'"),
+            Self::MalformedEscape => write!(f, "malformed Unicode character escape sequence"),
+            Self::InvalidNumber(n) => write!(f, "Can't parse number {n}"),
+            Self::MisplacedSeparator(n) => {
+                write!(f, "Digit separators must be between two digits in {n}")
+            }
+            Self::ShadowedName(name) => write!(f, "Can't shadow name '{name}'"),
+            Self::InvalidTailCall => write!(f, "'=>' can only be used on function calls"),
+        }
+    }
+}
+
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}:{}] {}", self.line, self.column, self.message)
+        write!(f, "[{}:{}] {}", self.line, self.column, self.kind)
     }
 }
 
 impl ParseError {
-    pub(crate) fn throw<T>(line: usize, column: usize, message: String) -> Result<T, Self> {
-        Err(Self {
-            line,
-            column,
-            message: Box::leak(message.into_boxed_str()),
-        })
+    pub(crate) fn throw<T>(line: usize, column: usize, kind: ParseErrorKind) -> Result<T, Self> {
+        Err(Self { line, column, kind })
+    }
+
+    /// The line where the error happened
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column where the error happened
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// What went wrong
+    #[must_use]
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
     }
 }
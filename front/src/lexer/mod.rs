@@ -1,10 +1,22 @@
 use vm::Symbol;
 
-use crate::error::ParseError;
-use crate::tokens::{fetch_keyword, Token, TokenType};
+use crate::error::{ParseError, ParseErrorKind};
+use crate::tokens::{fetch_keyword, TemplatePart, Token, TokenType};
 
 const EOF: char = '\0';
 
+/// Checks that every `_` in a number literal sits between two digits
+fn separators_between_digits(n: &str, radix: u32) -> bool {
+    let chars: Vec<char> = n.chars().collect();
+
+    chars.iter().enumerate().all(|(idx, c)| {
+        *c != '_'
+            || idx > 0
+                && chars[idx - 1].is_digit(radix)
+                && chars.get(idx + 1).is_some_and(|c| c.is_digit(radix))
+    })
+}
+
 #[derive(Clone)]
 pub struct Lexer {
     line: usize,
@@ -35,8 +47,8 @@ impl Lexer {
         self.idx = idx;
     }
 
-    fn throw<A, T: Into<String>>(&self, str: T) -> Result<A, ParseError> {
-        ParseError::throw(self.line, self.column, str.into())
+    fn throw<A>(&self, kind: ParseErrorKind) -> Result<A, ParseError> {
+        ParseError::throw(self.line, self.column, kind)
     }
 
     fn get_char(&self, idx: usize) -> char {
@@ -69,7 +81,7 @@ impl Lexer {
 
         while cond(self.get_char(self.idx + 1)) {
             if self.get_char(self.idx + 1) == '\0' {
-                self.throw("Unclosed delimiter opened here")?;
+                self.throw(ParseErrorKind::UnclosedDelimiter)?;
             }
             self.next();
             item.push(self.current());
@@ -82,7 +94,7 @@ impl Lexer {
         let mut unicode = String::new();
         while unicode.len() < len {
             if !self.current().is_ascii_hexdigit() {
-                return self.throw("malformed Unicode character escape sequence");
+                return self.throw(ParseErrorKind::MalformedEscape);
             }
             unicode.push(self.current());
             self.next();
@@ -103,14 +115,16 @@ impl Lexer {
             '0' => EOF,
             '\\' => '\\',
             '"' => '"',
+            '$' => '$',
             'r' => '\r',
-            other => self.throw(format!("Unknow escape char '{}'", other))?,
+            other => self.throw(ParseErrorKind::UnknownEscape(other))?,
         };
         self.next();
         Ok(char.into())
     }
 
-    fn take_str(&mut self) -> Result<String, ParseError> {
+    fn take_str(&mut self) -> Result<TokenType, ParseError> {
+        let mut parts = vec![];
         let mut item = String::new();
 
         while self.current() != '"' {
@@ -119,7 +133,12 @@ impl Lexer {
                     self.next();
                     self.escape_char()?
                 }
-                EOF => self.throw("Unclosed delimiter opened here")?,
+                '$' if self.peek_at(1) == '{' => {
+                    parts.push(TemplatePart::Str(std::mem::take(&mut item)));
+                    parts.push(self.take_interpolation()?);
+                    continue;
+                }
+                EOF => self.throw(ParseErrorKind::UnclosedDelimiter)?,
                 other => {
                     let other = other.to_string();
                     self.next();
@@ -130,13 +149,130 @@ impl Lexer {
             item.push_str(&chr);
         }
         self.back();
+
+        if parts.is_empty() {
+            return Ok(TokenType::Str(item));
+        }
+        parts.push(TemplatePart::Str(item));
+        Ok(TokenType::Template(parts))
+    }
+
+    /// Takes a `"""` string, which keeps newlines and backslashes as they are
+    fn take_raw_str(&mut self) -> Result<String, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.next();
+        self.next();
+        self.next();
+
+        let mut item = String::new();
+        while !(self.current() == '"' && self.peek_at(1) == '"' && self.peek_at(2) == '"') {
+            if self.current() == EOF {
+                return ParseError::throw(line, column, ParseErrorKind::UnclosedDelimiter);
+            }
+            item.push(self.current());
+            self.next();
+        }
+        self.next();
+        self.next();
+
         Ok(item)
     }
 
+    /// Takes the source of a `${...}` interpolation, which can have nested braces and strings
+    fn take_interpolation(&mut self) -> Result<TemplatePart, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.next();
+        self.next();
+
+        let (src_line, src_column) = (self.line, self.column);
+        let mut src = String::new();
+        let mut depth = 0;
+        let mut in_str = false;
+
+        loop {
+            match self.current() {
+                EOF => return ParseError::throw(line, column, ParseErrorKind::UnclosedDelimiter),
+                '\\' if in_str => {
+                    src.push(self.current());
+                    self.next();
+                }
+                '"' => in_str = !in_str,
+                '{' if !in_str => depth += 1,
+                '}' if !in_str && depth == 0 => break,
+                '}' if !in_str => depth -= 1,
+                _ => (),
+            }
+            src.push(self.current());
+            self.next();
+        }
+        self.next();
+
+        Ok(TemplatePart::Expr {
+            src,
+            line: src_line,
+            column: src_column,
+        })
+    }
+
     fn peek_at(&self, n: usize) -> char {
         *self.tokens.get(self.idx + n).unwrap_or(&EOF)
     }
 
+    /// Lexes a `0x`, `0o` or `0b` integer literal, errors point to the first invalid digit
+    fn radix_number(&mut self) -> Result<TokenType, ParseError> {
+        let mut literal = String::from('0');
+        let radix = match self.next() {
+            'x' => 16,
+            'o' => 8,
+            _ => 2,
+        };
+        literal.push(self.current());
+
+        while self.peek_at(1).is_alphanumeric() || self.peek_at(1) == '_' {
+            literal.push(self.next());
+            if !self.current().is_digit(radix) && self.current() != '_' {
+                return self.throw(ParseErrorKind::InvalidNumber(literal));
+            }
+        }
+
+        if !separators_between_digits(&literal[2..], radix) {
+            return self.throw(ParseErrorKind::MisplacedSeparator(literal));
+        }
+
+        match u64::from_str_radix(&literal[2..].replace('_', ""), radix) {
+            Ok(n) => Ok(TokenType::Num(n as f64)),
+            Err(_) => self.throw(ParseErrorKind::InvalidNumber(literal)),
+        }
+    }
+
+    /// Skips a `/* */` comment, which can be nested. Errors point to where the comment was opened
+    fn skip_block_comment(&mut self) -> Result<(), ParseError> {
+        let (line, column) = (self.line, self.column);
+        let mut depth = 0;
+
+        loop {
+            match (self.current(), self.peek_at(1)) {
+                ('/', '*') => {
+                    self.next();
+                    depth += 1;
+                }
+                ('*', '/') => {
+                    self.next();
+                    depth -= 1;
+                }
+                (EOF, _) => {
+                    return ParseError::throw(line, column, ParseErrorKind::UnclosedComment)
+                }
+                _ => (),
+            }
+            self.next();
+
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
     fn get(&mut self) -> Tk {
         let tk = match self.current() {
             // comments
@@ -146,6 +282,10 @@ impl Lexer {
                 }
                 return self.get();
             }
+            '/' if self.peek_at(1) == '*' => {
+                self.skip_block_comment()?;
+                return self.get();
+            }
 
             '+' => TokenType::Add,
             '-' if self.peek_at(1) == '>' => {
@@ -156,6 +296,10 @@ impl Lexer {
 
             '/' => TokenType::Div,
             '*' => TokenType::Mul,
+            '%' if self.peek_at(1) == '{' => {
+                self.next();
+                TokenType::Percent
+            }
             '%' => TokenType::Rem,
             '=' if self.peek_at(1) == '=' => {
                 self.next();
@@ -180,6 +324,14 @@ impl Lexer {
                 self.next();
                 TokenType::Cons
             }
+            // right after a name, like `x:Num` or `%{a:1}`, it's an annotation or a field
+            ':' if self.idx > 0 && {
+                let prev = self.get_char(self.idx - 1);
+                prev.is_alphanumeric() || prev == '_'
+            } =>
+            {
+                TokenType::Colon
+            }
             ':' if self.peek_at(1).is_alphanumeric() => {
                 self.next();
 
@@ -190,21 +342,36 @@ impl Lexer {
             ':' => TokenType::Colon,
 
             '=' => TokenType::Assign,
+            '"' if self.peek_at(1) == '"' && self.peek_at(2) == '"' => {
+                TokenType::Str(self.take_raw_str()?)
+            }
             '"' if self.peek_at(1) == '"' => {
                 self.next();
                 TokenType::Str(String::new())
             }
             '"' => {
                 self.next();
-                let a = TokenType::Str(self.take_str()?);
+                let a = self.take_str()?;
                 self.next();
                 a
             }
+            '0' if matches!(self.peek_at(1), 'x' | 'o' | 'b') => self.radix_number()?,
             c if c.is_numeric() => {
-                let n = self.take_while(|c| c.is_numeric() || c == '.')?;
-                match n.parse::<f64>() {
+                // stops before `..`, so `1..5` is a range and not a malformed number
+                let mut n = String::from(c);
+                while self.peek_at(1).is_numeric()
+                    || self.peek_at(1) == '_'
+                    || self.peek_at(1) == '.' && self.peek_at(2) != '.'
+                {
+                    n.push(self.next());
+                }
+                if !separators_between_digits(&n, 10) {
+                    self.throw(ParseErrorKind::MisplacedSeparator(n.clone()))?;
+                }
+
+                match n.replace('_', "").parse::<f64>() {
                     Ok(n) => TokenType::Num(n),
-                    Err(_) => self.throw(format!("Can't parse number {}", n))?,
+                    Err(_) => self.throw(ParseErrorKind::InvalidNumber(n))?,
                 }
             }
             c if c.is_alphabetic() || c == '_' => {
@@ -265,6 +432,15 @@ impl Lexer {
                 TokenType::GreaterEq
             }
             '>' => TokenType::Greater,
+            '.' if self.peek_at(1) == '.' && self.peek_at(2) == '=' => {
+                self.next();
+                self.next();
+                TokenType::DotDotEq
+            }
+            '.' if self.peek_at(1) == '.' => {
+                self.next();
+                TokenType::DotDot
+            }
             '.' => TokenType::Dot,
 
             // Logical
@@ -277,6 +453,7 @@ impl Lexer {
                 TokenType::Or
             }
             '!' => TokenType::Not,
+            '#' => TokenType::Len,
 
             '|' if self.peek_at(1) == '>' => {
                 self.next();
@@ -291,7 +468,7 @@ impl Lexer {
                 return self.get();
             }
 
-            c => self.throw(format!("Unknown start of token '{}'", c))?,
+            c => self.throw(ParseErrorKind::UnknownChar(c))?,
         };
 
         Ok(Token {
@@ -332,3 +509,165 @@ fn parse_string() {
     let string = Lexer::new(r#""Hello, world!""#).get().unwrap();
     assert_eq!(string.token, TokenType::Str(String::from("Hello, world!")))
 }
+
+#[test]
+fn unknown_char() {
+    let err = Lexer::new("@").get().unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::UnknownChar('@'));
+}
+
+#[test]
+fn block_comment() {
+    let token = Lexer::new("/* a\n comment */ 1").get().unwrap();
+    assert_eq!(token.token, TokenType::Num(1.0));
+}
+
+#[test]
+fn nested_block_comment() {
+    let tokens = Lexer::new("/* a /* b */ c */ 1 /**/ 2")
+        .map(|tk| tk.unwrap().token)
+        .take(3)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        [TokenType::Num(1.0), TokenType::Num(2.0), TokenType::Eof]
+    );
+}
+
+#[test]
+fn unclosed_block_comment() {
+    let err = Lexer::new("1\n  /* a /* b */").nth(1).unwrap().unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::UnclosedComment);
+    assert_eq!(err.line(), 2);
+}
+
+#[test]
+fn radix_numbers() {
+    for (input, n) in [
+        ("0xFF", 255.0),
+        ("0xFF_FF", 65535.0),
+        ("0b1010_1010", 170.0),
+        ("0b1010", 10.0),
+        ("0o17", 15.0),
+        ("0", 0.0),
+    ] {
+        let token = Lexer::new(input).get().unwrap();
+        assert_eq!(token.token, TokenType::Num(n));
+    }
+}
+
+#[test]
+fn invalid_radix_digit() {
+    let err = Lexer::new("0xG").get().unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &ParseErrorKind::InvalidNumber("0xG".to_string())
+    );
+    assert_eq!(err.column(), 3);
+
+    let err = Lexer::new("0x").get().unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::InvalidNumber("0x".to_string()));
+}
+
+#[test]
+fn digit_separators() {
+    for (input, n) in [
+        ("1_000", 1000.0),
+        ("1.0_5", 1.05),
+        ("1_000_000.5", 1_000_000.5),
+    ] {
+        let token = Lexer::new(input).get().unwrap();
+        assert_eq!(token.token, TokenType::Num(n));
+    }
+
+    for input in ["1_", "1__0", "1_.5", "1._5"] {
+        let err = Lexer::new(input).get().unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ParseErrorKind::MisplacedSeparator(input.to_string())
+        );
+    }
+
+    for input in ["0x_FF", "0xFF_", "0b1__0"] {
+        let err = Lexer::new(input).get().unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ParseErrorKind::MisplacedSeparator(input.to_string())
+        );
+    }
+
+    // a leading underscore starts a name
+    let token = Lexer::new("_1").get().unwrap();
+    assert_eq!(token.token, TokenType::Name(Symbol::new("_1")));
+}
+
+#[test]
+fn raw_string() {
+    let tokens: Vec<_> = Lexer::new("\"\"\"say \"hi\"\n\\n ${x}\"\"\"\n  x")
+        .take(2)
+        .map(Result::unwrap)
+        .collect();
+
+    let (string, name) = (&tokens[0], &tokens[1]);
+    assert_eq!(
+        string.token,
+        TokenType::Str("say \"hi\"\n\\n ${x}".to_string())
+    );
+    assert_eq!(name.token, TokenType::Name(Symbol::new("x")));
+    assert_eq!(name.line, 3);
+}
+
+#[test]
+fn unclosed_raw_string() {
+    let err = Lexer::new("1\n\"\"\"abc\"\"").nth(1).unwrap().unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::UnclosedDelimiter);
+    assert_eq!(err.line(), 2);
+}
+
+#[test]
+fn range_operators() {
+    for (input, op) in [("1..5", TokenType::DotDot), ("1..=5", TokenType::DotDotEq)] {
+        let tokens: Vec<_> = Lexer::new(input)
+            .take(3)
+            .map(|tk| tk.unwrap().token)
+            .collect();
+        assert_eq!(tokens, [TokenType::Num(1.0), op, TokenType::Num(5.0)]);
+    }
+
+    let tokens: Vec<_> = Lexer::new("1.5..x.y")
+        .take(5)
+        .map(|tk| tk.unwrap().token)
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            TokenType::Num(1.5),
+            TokenType::DotDot,
+            TokenType::Name(Symbol::new("x")),
+            TokenType::Dot,
+            TokenType::Name(Symbol::new("y")),
+        ]
+    );
+}
+
+#[test]
+fn colon_after_name() {
+    let tokens: Vec<_> = Lexer::new("x:Num %{a:1} :sym")
+        .take(9)
+        .map(|tk| tk.unwrap().token)
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            TokenType::Name(Symbol::new("x")),
+            TokenType::Colon,
+            TokenType::Name(Symbol::new("Num")),
+            TokenType::Percent,
+            TokenType::Name(Symbol::new("a")),
+            TokenType::Colon,
+            TokenType::Num(1.0),
+            TokenType::Rbrace,
+            TokenType::Sym(Symbol::new("sym")),
+        ]
+    );
+}
@@ -14,13 +14,16 @@ mod error;
 mod lexer;
 mod parser;
 mod tokens;
+mod typecheck;
 
 use compiler::Compiler;
-pub use error::ParseError;
+pub use error::{ParseError, ParseErrorKind};
+pub use typecheck::{TypeError, TypeErrorKind};
 
 use error::ParseResult;
 use lexer::Lexer;
 use parser::Parser;
+use typecheck::TypeChecker;
 use vm::{Bytecode, Value};
 
 /// Parses a given string into an AST
@@ -33,6 +36,36 @@ pub fn parse<T: Into<String>>(str: T) -> ParseResult<(Bytecode, Vec<Value>)> {
     Ok(compiler.compile_stmts(&ast))
 }
 
+/// Same as [`parse`], but globals can be redefined instead of raising a `NameError`, so the REPL
+/// can shadow previous definitions
+pub fn parse_permissive<T: Into<String>>(str: T) -> ParseResult<(Bytecode, Vec<Value>)> {
+    let lexer = Lexer::new(str);
+    let parser = Parser::new(lexer)?;
+    let ast = parser.parse()?;
+
+    let compiler = Compiler::permissive();
+    Ok(compiler.compile_stmts(&ast))
+}
+
+/// Parses the whole input, collecting every syntax error instead of stopping at the first one
+pub fn diagnostics<T: Into<String>>(str: T) -> Vec<ParseError> {
+    let lexer = Lexer::new(str);
+    match Parser::new(lexer) {
+        Ok(parser) => parser.parse_recovering().1,
+        Err(err) => vec![err],
+    }
+}
+
+/// Parses the input and checks it for type errors, without compiling it
+///
+/// This is an opt-in pass, [`parse`] doesn't run it
+pub fn typecheck<T: Into<String>>(str: T) -> ParseResult<Vec<TypeError>> {
+    let lexer = Lexer::new(str);
+    let ast = Parser::new(lexer)?.parse()?;
+
+    Ok(TypeChecker::default().check(&ast))
+}
+
 /// Parses the given string in a single expression
 pub fn parse_expr<T: Into<String>>(str: T) -> ParseResult<(Bytecode, Vec<Value>)> {
     let lexer = Lexer::new(str);
@@ -43,3 +76,259 @@ pub fn parse_expr<T: Into<String>>(str: T) -> ParseResult<(Bytecode, Vec<Value>)
     let compiler = Compiler::new();
     Ok(compiler.compile_expr(&ast))
 }
+
+#[test]
+fn permissive_globals_are_redefined() {
+    let mut vm = vm::VirtualMachine::default();
+
+    for src in ["def a x = x", "let a = 1", "let a = 2"] {
+        let (bt, ct) = parse_permissive(src).unwrap();
+        vm.set_consts(ct);
+        vm.run(&bt).unwrap();
+    }
+
+    assert_eq!(vm.get_global("a"), Some(Value::Num(2.0)));
+
+    let (bt, ct) = parse("let a = 3").unwrap();
+    vm.set_consts(ct);
+    assert!(vm.run(&bt).is_err());
+}
+
+#[test]
+fn builtins_cant_be_redefined() {
+    for src in [
+        "def assert message condition = condition",
+        "let println = 5",
+    ] {
+        let mut vm = vm::VirtualMachine::default();
+        let (bt, ct) = parse(src).unwrap();
+        vm.set_consts(ct);
+
+        let err = vm.run(&bt).unwrap_err();
+        assert_eq!(err.err, vm::Symbol::from("NameError"));
+    }
+}
+
+#[test]
+fn unbounded_recursion_is_a_stack_overflow() {
+    // runs on the test's own thread, which has the default stack size
+    let src = "def down n = if n == 0 then :bottom else down (n - 1)
+def g x = g x + 1
+def through_map n = List.map (through_map_ (n + 1)) [n]
+def through_map_ n _ = through_map n
+let deep = down 4000
+let rescued = try g 1 rescue e e
+let callbacks = try through_map 0 rescue e e
+let overflow = g 1";
+
+    let mut vm = vm::VirtualMachine::default();
+    let (bt, ct) = parse(src).unwrap();
+    vm.set_consts(ct);
+
+    let err = vm.run(&bt).unwrap_err();
+    assert_eq!(err.err, vm::Symbol::from("StackOverflow"));
+    assert_eq!(err.line, 2);
+    assert_eq!(
+        vm.get_global("deep"),
+        Some(Value::Sym(vm::Symbol::from("bottom").into()))
+    );
+    assert_eq!(
+        vm.get_global("rescued"),
+        Some(Value::Sym(vm::Symbol::from("StackOverflow").into()))
+    );
+    assert_eq!(
+        vm.get_global("callbacks"),
+        Some(Value::Sym(vm::Symbol::from("StackOverflow").into()))
+    );
+}
+
+#[test]
+fn recursion_depth_can_be_limited() {
+    let run = |depth| {
+        let src = "def count n = if n == 0 then 0 else 1 + count (n - 1)
+let result = count 10";
+
+        let mut vm = vm::VirtualMachine::default();
+        vm.set_max_depth(depth);
+        let (bt, ct) = parse(src).unwrap();
+        vm.set_consts(ct);
+        vm.run(&bt).map(|()| vm.get_global("result"))
+    };
+
+    // `count 10` nests 11 calls
+    assert_eq!(run(11).unwrap(), Some(Value::Num(10.0)));
+
+    let err = run(10).unwrap_err();
+    assert_eq!(err.err, vm::Symbol::from("RecursionError"));
+}
+
+#[test]
+fn sum_type_variants_can_be_matched() {
+    let src = "type Shape = circle r | rect w h with end
+def area shape = match shape with
+    | Shape.circle r -> 3 * r * r
+    | Shape.rect w h -> w * h
+let circle = area (Shape.circle 2)
+let rect = area (Shape.rect 2 5)";
+
+    let mut vm = vm::VirtualMachine::default();
+    let (bt, ct) = parse(src).unwrap();
+    vm.set_consts(ct);
+    vm.run(&bt).unwrap();
+
+    assert_eq!(vm.get_global("circle"), Some(Value::Num(12.0)));
+    assert_eq!(vm.get_global("rect"), Some(Value::Num(10.0)));
+}
+
+#[test]
+fn tasks_interleave_their_output() {
+    use std::{cell::RefCell, io, rc::Rc};
+
+    #[derive(Clone, Default)]
+    struct Captured(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let src = "def worker name n () =
+    let _ = print name
+    in let _ = print n
+    in if n == 0 then name else yield (worker name (n - 1))
+let _ = spawn (worker \"a\" 2)
+let _ = spawn (worker \"b\" 1)
+let finished = runTasks ()";
+
+    let captured = Captured::default();
+    let mut vm = vm::VirtualMachine::default();
+    vm.set_output(captured.clone());
+    let (bt, ct) = parse(src).unwrap();
+    vm.set_consts(ct);
+    vm.run(&bt).unwrap();
+
+    assert_eq!(captured.0.borrow().as_slice(), b"a2b1a1b0a0");
+    assert_eq!(
+        vm.get_global("finished"),
+        Some(Value::List(
+            vm::List::new()
+                .prepend(Value::from("a".to_string()))
+                .prepend(Value::from("b".to_string()))
+        ))
+    );
+}
+
+#[test]
+fn serialized_programs_run_the_same() {
+    let src = "type Shape = circle r | rect w h with end
+def area shape = match shape with
+    | Shape.circle r -> 3 * r * r
+    | Shape.rect w h -> w * h
+let point = %{x: 1, y: \"two\"}
+let result = (List.map area [Shape.circle 1, Shape.rect 2 3], point, try 1 + nil rescue e e)";
+
+    let run = |(bt, ct): (vm::Bytecode, Vec<Value>)| {
+        let mut vm = vm::VirtualMachine::default();
+        vm.set_consts(ct);
+        vm.run(&bt).unwrap();
+        vm.get_global("result")
+    };
+
+    let (bt, ct) = parse(src).unwrap();
+    let bytes = vm::serialize_program(&bt, &ct).unwrap();
+    let loaded = vm::deserialize_program(&bytes).unwrap();
+
+    let expected = run(parse(src).unwrap());
+    assert!(expected.is_some());
+    assert_eq!(run(loaded), expected);
+}
+
+#[test]
+fn shown_values_read_back_the_same() {
+    use vm::{List, Struct, Symbol, Tuple};
+
+    fn value(depth: usize, next: &mut dyn FnMut(usize) -> usize) -> Value {
+        let nums = [0.0, -1.5, 0.1 + 0.2, 1e300, 1e-7, 9_007_199_254_740_993.0];
+        let strs = [
+            "",
+            "say \"hi\"\n",
+            "tab\t\\ ${x} $5",
+            "\r\u{1}\0",
+            "héllo 🦀",
+        ];
+        let syms = ["a", "TypeError", "snake_case"];
+
+        let kind = if depth == 0 { next(5) } else { next(8) };
+        match kind {
+            0 => Value::Num(nums[next(nums.len())]),
+            1 => strs[next(strs.len())].to_string().into(),
+            2 => Symbol::from(syms[next(syms.len())]).into(),
+            3 => Value::Bool(next(2) == 0),
+            4 => Value::Nil,
+            5 => {
+                let xs: Vec<_> = (0..next(4)).map(|_| value(depth - 1, next)).collect();
+                Value::List(xs.into_iter().rev().collect::<List>())
+            }
+            // a one element tuple has no syntax, `(x)` is just `x`
+            6 => {
+                let len = [0, 2, 3][next(3)];
+                let xs: Vec<_> = (0..len).map(|_| value(depth - 1, next)).collect();
+                Value::Tuple(Tuple::from(xs))
+            }
+            _ => {
+                let fields: Vec<_> = ["x", "y", "z"][..next(4)]
+                    .iter()
+                    .map(|name| (Symbol::from(name), value(depth - 1, next)))
+                    .collect();
+                Value::Struct(Struct::from(fields))
+            }
+        }
+    }
+
+    // a fixed xorshift, so failures are reproducible
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move |n: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        usize::try_from(state % n as u64).unwrap()
+    };
+
+    for _ in 0..200 {
+        let expected = value(3, &mut next);
+        let src = format!("let result = {expected}");
+
+        let (bt, ct) = parse(&*src).unwrap_or_else(|e| panic!("{src}: {e}"));
+        let mut vm = vm::VirtualMachine::default();
+        vm.set_consts(ct);
+        vm.run(&bt).unwrap();
+
+        assert_eq!(vm.get_global("result"), Some(expected), "{src}");
+    }
+}
+
+#[test]
+fn rescue_arms_keep_the_error_with_over() {
+    let src = "let raised = try raise :Boom \"details\" rescue | :Boom -> 1 | _ -> 2
+let thrown = try throw (:bad, 3) rescue | (:bad, n) -> n | _ -> 0
+let unmatched = try (try raise :Boom \"details\" rescue | :Other -> 0) rescue (_, msg) msg";
+
+    let mut vm = vm::VirtualMachine::default();
+    let (bt, ct) = parse(src).unwrap();
+    assert!(bt.iter().any(|op| op.opcode == vm::OpCode::Over));
+
+    vm.set_consts(ct);
+    vm.run(&bt).unwrap();
+
+    assert_eq!(vm.get_global("raised"), Some(Value::Num(1.0)));
+    assert_eq!(vm.get_global("thrown"), Some(Value::Num(3.0)));
+    assert_eq!(
+        vm.get_global("unmatched"),
+        Some(Value::from("details".to_string()))
+    );
+}
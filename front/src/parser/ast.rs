@@ -20,6 +20,49 @@ pub enum Pattern {
     Tuple(Vec<Pattern>),
     List(Box<Self>, Box<Self>),
     EmptyList,
+    Struct(Vec<(Symbol, Pattern)>),
+}
+
+impl Pattern {
+    /// Every name bound by the pattern, in order
+    pub fn names(&self) -> Vec<VarDecl> {
+        match self {
+            Self::Id(name) if name.as_str() != "_" => vec![*name],
+            Self::Id(_) | Self::Lit(_) | Self::EmptyList => vec![],
+            Self::Variant(_, pats) | Self::Tuple(pats) => {
+                pats.iter().flat_map(Self::names).collect()
+            }
+            Self::List(head, tail) => [head.names(), tail.names()].concat(),
+            Self::Struct(fields) => fields.iter().flat_map(|(_, pat)| pat.names()).collect(),
+        }
+    }
+}
+
+/// A type annotation, like the `Num` in `let x: Num = 1`
+#[derive(Debug, Clone)]
+pub enum Type {
+    Name(Path),
+    List(Box<Self>),
+    Tuple(Vec<Self>),
+    Fn(Box<Self>, Box<Self>),
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(path) => {
+                let path: Vec<_> = path.iter().map(Symbol::as_str).collect();
+                write!(f, "{}", path.join("."))
+            }
+            Self::List(ty) => write!(f, "[{ty}]"),
+            Self::Tuple(tys) => {
+                let tys: Vec<_> = tys.iter().map(ToString::to_string).collect();
+                write!(f, "({})", tys.join(", "))
+            }
+            Self::Fn(arg, ret) if matches!(**arg, Self::Fn(..)) => write!(f, "({arg}) -> {ret}"),
+            Self::Fn(arg, ret) => write!(f, "{arg} -> {ret}"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -102,6 +145,7 @@ impl TryFrom<TokenType> for BinOp {
 pub enum UnOp {
     Not,
     Neg,
+    Len,
 }
 
 impl TryFrom<TokenType> for UnOp {
@@ -111,6 +155,7 @@ impl TryFrom<TokenType> for UnOp {
         match t {
             TokenType::Not => Ok(UnOp::Not),
             TokenType::Sub => Ok(UnOp::Neg),
+            TokenType::Len => Ok(UnOp::Len),
             _ => Err(()),
         }
     }
@@ -121,6 +166,7 @@ impl<'a> From<UnOp> for &'a [OpCode] {
         match op {
             UnOp::Not => &[OpCode::Not],
             UnOp::Neg => &[OpCode::Neg],
+            UnOp::Len => &[OpCode::Len],
         }
     }
 }
@@ -161,17 +207,27 @@ impl MatchArm {
     }
 }
 
+/// What handles the error symbol raised inside a `try`
+#[derive(Debug)]
+pub enum Rescue {
+    /// `rescue e body` binds the error symbol, any other pattern, like `rescue (e, msg) body`, is
+    /// matched against the `(symbol, message)` pair
+    Bind(Pattern, Box<Expr>),
+    /// `rescue | :TypeError -> a | _ -> b` matches the symbol, errors that no arm matches are
+    /// raised again
+    Arms(Vec<MatchArm>),
+}
+
 #[derive(Debug)]
 pub enum ExprKind {
     If {
         cond: Box<Expr>,
         then: Box<Expr>,
-        else_: Box<Expr>,
+        else_: Option<Box<Expr>>, // `nil` when missing
     },
 
     Let {
-        bind: Pattern,
-        value: Box<Expr>,
+        binds: Vec<(Pattern, Option<Type>, Expr)>, // the `and` separated bindings
         body: Box<Expr>,
     },
     Def {
@@ -185,8 +241,10 @@ pub enum ExprKind {
     },
 
     Lambda {
-        args: Vec<Pattern>, // specifies the arguments name and types
-        body: Box<Expr>,    // the function body
+        args: Vec<Pattern>,           // specifies the arguments name
+        arg_types: Vec<Option<Type>>, // the annotated type of each argument
+        ret: Option<Type>,            // the annotated return type
+        body: Box<Expr>,              // the function body
     },
 
     App {
@@ -201,6 +259,8 @@ pub enum ExprKind {
     },
 
     Var(Symbol),
+    /// A global that locals can't shadow, what desugarings call builtins through
+    Global(Symbol),
     Lit(Literal),
     List(Vec<Expr>),
 
@@ -218,11 +278,23 @@ pub enum ExprKind {
 
     Try {
         body: Box<Expr>,
-        bind: VarDecl,
-        rescue: Box<Expr>,
+        rescue: Rescue,
+    },
+
+    /// `[elem for pat in iter if filter]`, with one `(pat, iter)` per generator
+    Comprehension {
+        elem: Box<Expr>,
+        generators: Vec<(Pattern, Expr)>,
+        filter: Option<Box<Expr>>,
     },
 
     Tuple(Vec<Expr>),
+    Struct(Vec<(Symbol, Expr)>),
+    /// `%{base with field: value}`, a copy of `base` with some of its fields replaced
+    StructUpdate {
+        base: Box<Expr>,
+        fields: Vec<(Symbol, Expr)>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -317,6 +389,7 @@ pub enum StmtKind {
     Def(Def),
     Let {
         bind: Pattern,
+        ty: Option<Type>,
         value: Expr,
     },
     Type {
@@ -3,24 +3,62 @@ use std::{collections::HashSet, mem::take};
 use vm::Symbol;
 
 use crate::{
-    error::{ParseError, ParseResult},
+    error::{ParseError, ParseErrorKind, ParseResult},
     lexer::Lexer,
-    tokens::{Token, TokenType as Tkt},
+    tokens::{TemplatePart, Token, TokenType as Tkt},
 };
 
-use self::ast::{Bind, Def, Expr, ExprKind, Literal, MatchArm, Pattern, Stmt, StmtKind, VarDecl};
+use self::ast::{
+    Bind, Def, Expr, ExprKind, Literal, Location, MatchArm, Pattern, Rescue, Stmt, StmtKind, Type,
+    VarDecl,
+};
 
 pub mod ast;
 
+/// A function parameter, with the names it binds and its annotated type
+type Param = (Vec<Symbol>, Pattern, Option<Type>);
+
+/// Tokens that can start an expression, reported when `primary` fails
+const EXPR_START: &[&str] = &[
+    "number", "string", "symbol", "name", "true", "false", "nil", "(", "[", "%{", "let", "def",
+    "if", "fn", "=>", "match", "try", "-", "!", "#",
+];
+
+/// Finds the positions of the `let`s and `def`s closed by an `in`, the others start a statement
+fn bindings_with_in(mut lexer: Lexer) -> HashSet<(usize, usize)> {
+    let mut open = vec![];
+    let mut closed = HashSet::new();
+
+    while let Some(Ok(token)) = lexer.next() {
+        match token.token {
+            Tkt::Let | Tkt::Def => open.push(Some((token.line, token.column))),
+            // the `in` of a comprehension's `for`
+            Tkt::For => open.push(None),
+            Tkt::In => {
+                if let Some(Some(position)) = open.pop() {
+                    closed.insert(position);
+                }
+            }
+            Tkt::Eof => break,
+            _ => {}
+        }
+    }
+
+    closed
+}
+
 pub struct Parser {
     lexer: Lexer,
     current: Token,
     locals: HashSet<Symbol>,
+    /// `let`s and `def`s that have an `in`, so they are expressions instead of statements
+    expr_bindings: HashSet<(usize, usize)>,
 }
 
 impl Parser {
     pub fn new(lexer: Lexer) -> ParseResult<Self> {
         let mut this = Parser {
+            expr_bindings: bindings_with_in(lexer.clone()),
             lexer,
             current: Token::default(),
             locals: HashSet::new(),
@@ -40,13 +78,51 @@ impl Parser {
                 Tkt::Def => stmts.push(self.def_global()?),
                 Tkt::Let => stmts.push(self.let_global()?),
 
-                ref other => self.throw(format!("Unexpected token '{other}'"))?,
+                _ => self.unexpected("statement")?,
             }
         }
 
         Ok(stmts)
     }
 
+    /// Parses every statement, skipping to the next `def`, `let` or `type` when one of them fails
+    pub fn parse_recovering(mut self) -> (Vec<Stmt>, Vec<ParseError>) {
+        let mut stmts = Vec::new();
+        let mut errors = Vec::new();
+
+        while self.current.token != Tkt::Eof {
+            let stmt = match self.current.token {
+                Tkt::Type => self.type_(),
+                Tkt::Def => self.def_global(),
+                Tkt::Let => self.let_global(),
+                _ => self.unexpected("statement"),
+            };
+
+            match stmt {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize(&mut errors);
+                }
+            }
+        }
+
+        (stmts, errors)
+    }
+
+    fn synchronize(&mut self, errors: &mut Vec<ParseError>) {
+        loop {
+            if let Err(err) = self.next() {
+                errors.push(err);
+                continue;
+            }
+
+            if let Tkt::Def | Tkt::Let | Tkt::Type | Tkt::Eof = self.current.token {
+                break;
+            }
+        }
+    }
+
     pub fn let_global(&mut self) -> ParseResult<Stmt> {
         let line = self.current.line;
         let column = self.current.column;
@@ -56,12 +132,13 @@ impl Parser {
         self.locals = HashSet::new();
 
         let (_, bind) = self.pattern()?;
+        let ty = self.annotation()?;
 
         self.expect(&Tkt::Assign)?;
 
         let value = self.expr()?;
 
-        Ok(Stmt::new(StmtKind::Let { bind, value }, line, column))
+        Ok(Stmt::new(StmtKind::Let { bind, ty, value }, line, column))
     }
 
     pub fn parse_expr(mut self) -> ParseResult<Expr> {
@@ -148,8 +225,28 @@ impl Parser {
         Ok(token)
     }
 
-    fn throw<T>(&self, err: impl Into<String>) -> ParseResult<T> {
-        ParseError::throw(self.current.line, self.current.column, err.into())
+    fn throw<T>(&self, kind: ParseErrorKind) -> ParseResult<T> {
+        ParseError::throw(self.current.line, self.current.column, kind)
+    }
+
+    fn unexpected<T>(&self, expected: impl Into<String>) -> ParseResult<T> {
+        match self.current.token {
+            Tkt::Eof => self.throw(ParseErrorKind::UnexpectedEof),
+            ref found => self.throw(ParseErrorKind::UnexpectedToken {
+                expected: expected.into(),
+                found: found.to_string(),
+            }),
+        }
+    }
+
+    fn unexpected_one_of<T>(&self, expected: &[&str]) -> ParseResult<T> {
+        match self.current.token {
+            Tkt::Eof => self.throw(ParseErrorKind::UnexpectedEof),
+            ref found => self.throw(ParseErrorKind::ExpectedOneOf {
+                expected: expected.iter().map(ToString::to_string).collect(),
+                found: found.to_string(),
+            }),
+        }
     }
 
     fn expect(&mut self, expected: &Tkt) -> ParseResult<()> {
@@ -159,7 +256,7 @@ impl Parser {
 
     fn check_unused(&self, name: &Symbol) -> ParseResult<()> {
         if self.locals.contains(name) && name.as_str() != "_" {
-            self.throw(format!("Can't shadow name '{}'", name.as_str()))?;
+            self.throw(ParseErrorKind::ShadowedName(name.as_str().to_string()))?;
         }
 
         Ok(())
@@ -169,10 +266,7 @@ impl Parser {
         if &self.current.token == expected {
             Ok(())
         } else {
-            self.throw(format!(
-                "Expected {}, found '{}'",
-                expected, self.current.token
-            ))
+            self.unexpected(expected.to_string())
         }
     }
 
@@ -201,8 +295,11 @@ impl Parser {
         self.pipe()
     }
 
+    /// Parses an `if`, or the `elif` that continues one. Without an `else` the result is `nil`
     fn condition(&mut self) -> ParseResult<Expr> {
-        self.assert(&Tkt::If)?;
+        if self.current.token != Tkt::Elif {
+            self.assert(&Tkt::If)?;
+        }
 
         self.next()?;
 
@@ -218,36 +315,128 @@ impl Parser {
         let else_ = match self.current.token {
             Tkt::Else => {
                 self.next()?;
-                self.expr()?
+                Some(Box::new(self.expr()?))
             }
-            _ => self.throw("Expected 'else' after 'if'")?,
+            Tkt::Elif => Some(Box::new(self.condition()?)),
+            _ => None,
         };
 
         Ok(Expr::new(
             ExprKind::If {
                 cond: Box::new(cond),
                 then: Box::new(then),
-                else_: Box::new(else_),
+                else_,
             },
             line,
             column,
         ))
     }
 
-    fn args(&mut self, is_lambda: bool) -> ParseResult<Vec<(Vec<Symbol>, Pattern)>> {
-        let mut args = vec![self.primary_pat()?];
+    fn args(&mut self, is_lambda: bool) -> ParseResult<Vec<Param>> {
+        let mut args = vec![self.param()?];
         if is_lambda {
-            while self.current.token != Tkt::Arrow {
-                args.push(self.primary_pat()?);
+            while !matches!(self.current.token, Tkt::Arrow | Tkt::Colon) {
+                args.push(self.param()?);
             }
         } else {
-            while self.current.token != Tkt::Assign {
-                args.push(self.primary_pat()?);
+            while !matches!(self.current.token, Tkt::Assign | Tkt::Colon) {
+                args.push(self.param()?);
             }
         }
         Ok(args)
     }
 
+    /// A function parameter, optionally annotated like `(x: Num)`
+    fn param(&mut self) -> ParseResult<Param> {
+        if self.current.token == Tkt::Lparen {
+            let state = self.state();
+            self.next()?;
+
+            if let Ok((ids, pat)) = self.pattern() {
+                if self.current.token == Tkt::Colon {
+                    self.next()?;
+                    let ty = self.fn_type()?;
+                    self.expect(&Tkt::Rparen)?;
+                    return Ok((ids, pat, Some(ty)));
+                }
+            }
+
+            self.set_state(state);
+        }
+
+        let (ids, pat) = self.primary_pat()?;
+        Ok((ids, pat, None))
+    }
+
+    /// Parses an optional `: Type` annotation
+    fn annotation(&mut self) -> ParseResult<Option<Type>> {
+        if self.current.token != Tkt::Colon {
+            return Ok(None);
+        }
+
+        self.next()?;
+        Ok(Some(self.type_expr()?))
+    }
+
+    /// A type, where `->` builds function types, right associative
+    fn fn_type(&mut self) -> ParseResult<Type> {
+        let ty = self.type_expr()?;
+
+        if self.current.token == Tkt::Arrow {
+            self.next()?;
+            let ret = self.fn_type()?;
+            return Ok(Type::Fn(Box::new(ty), Box::new(ret)));
+        }
+
+        Ok(ty)
+    }
+
+    /// A type without top level arrows, so it doesn't eat the `->` of a lambda
+    fn type_expr(&mut self) -> ParseResult<Type> {
+        let ty = match self.current.token {
+            Tkt::Name(_) => {
+                let mut path = vec![self.var_decl()?];
+                while let Tkt::Dot = self.current.token {
+                    self.next()?;
+                    path.push(self.var_decl()?);
+                }
+
+                return Ok(Type::Name(path));
+            }
+            Tkt::Lbrack => {
+                self.next()?;
+                let ty = self.fn_type()?;
+                self.assert(&Tkt::Rbrack)?;
+
+                Type::List(Box::new(ty))
+            }
+            Tkt::Lparen => {
+                self.next()?;
+
+                let mut tys = vec![];
+                while self.current.token != Tkt::Rparen {
+                    tys.push(self.fn_type()?);
+
+                    if self.current.token != Tkt::Rparen {
+                        self.expect_and_skip(&Tkt::Comma)?;
+                    }
+                }
+                self.assert(&Tkt::Rparen)?;
+
+                if tys.len() == 1 {
+                    tys.pop().unwrap()
+                } else {
+                    Type::Tuple(tys)
+                }
+            }
+            _ => self.unexpected("type")?,
+        };
+
+        self.next()?;
+
+        Ok(ty)
+    }
+
     fn become_(&mut self) -> ParseResult<Expr> {
         self.expect(&Tkt::FatArrow)?;
 
@@ -266,7 +455,7 @@ impl Parser {
                 line,
                 column,
             )),
-            _ => self.throw("'=>' can only be used on function calls"),
+            _ => self.throw(ParseErrorKind::InvalidTailCall),
         }
     }
 
@@ -280,6 +469,12 @@ impl Parser {
 
         self.expect(&Tkt::With)?;
 
+        let arms = self.match_arms()?;
+
+        Ok(Expr::new(ExprKind::Match { expr, arms }, line, column))
+    }
+
+    fn match_arms(&mut self) -> ParseResult<Vec<MatchArm>> {
         let mut arms = vec![];
 
         let mut last_state = self.state();
@@ -294,7 +489,7 @@ impl Parser {
             self.match_arm()?;
         }
 
-        Ok(Expr::new(ExprKind::Match { expr, arms }, line, column))
+        Ok(arms)
     }
 
     fn match_arm(&mut self) -> ParseResult<MatchArm> {
@@ -332,15 +527,20 @@ impl Parser {
 
         self.expect(&Tkt::Rescue)?;
 
-        let bind = self.var_decl()?;
+        let rescue = if self.current.token == Tkt::Bar {
+            Rescue::Arms(self.match_arms()?)
+        } else {
+            // a sum pattern would swallow the rescue body, so those need parens
+            let (ids, bind) = self.primary_pat()?;
+            let rescue = self.expr()?;
 
-        let rescue = Box::new(self.expr()?);
+            for id in ids {
+                self.locals.remove(&id);
+            }
+            Rescue::Bind(bind, Box::new(rescue))
+        };
 
-        Ok(Expr::new(
-            ExprKind::Try { body, bind, rescue },
-            line,
-            column,
-        ))
+        Ok(Expr::new(ExprKind::Try { body, rescue }, line, column))
     }
 
     fn fn_(&mut self) -> ParseResult<Expr> {
@@ -356,14 +556,17 @@ impl Parser {
 
         let mut ids = vec![];
         let mut args = vec![];
+        let mut arg_types = vec![];
 
         let pats = self.args(is_lambda)?;
 
-        for (names, arg) in pats {
+        for (names, arg, ty) in pats {
             ids.extend(names);
             args.push(arg);
+            arg_types.push(ty);
         }
 
+        let ret = self.annotation()?;
         let body = self.fn_body(is_lambda)?;
 
         for id in ids {
@@ -375,6 +578,8 @@ impl Parser {
         Ok(Expr::new(
             ExprKind::Lambda {
                 args,
+                arg_types,
+                ret,
                 body: Box::new(body),
             },
             line,
@@ -394,7 +599,7 @@ impl Parser {
     fn var_decl(&mut self) -> ParseResult<VarDecl> {
         let name = match self.current.token {
             Tkt::Name(id) => id,
-            ref other => self.throw(format!("Expected name, found '{}'", other))?,
+            _ => self.unexpected("name")?,
         };
 
         self.next()?;
@@ -462,6 +667,7 @@ impl Parser {
         let pat = match self.current.token {
             Tkt::Num(n) => Pattern::Lit(Literal::Num(n)),
             Tkt::Str(ref s) => Pattern::Lit(Literal::Str(s.to_string())),
+            Tkt::Sym(s) => Pattern::Lit(Literal::Sym(s)),
             Tkt::Nil => Pattern::Lit(Literal::Unit),
             Tkt::True => Pattern::Lit(Literal::Bool(true)),
             Tkt::False => Pattern::Lit(Literal::Bool(false)),
@@ -494,7 +700,30 @@ impl Parser {
                 self.assert(&Tkt::Rbrack)?;
                 Pattern::EmptyList
             }
-            ref other => self.throw(format!("Expected pattern, found '{other}'"))?,
+            Tkt::Percent => {
+                self.next()?;
+
+                let mut fields = vec![];
+                let mut identifiers = vec![];
+
+                while self.current.token != Tkt::Rbrace {
+                    let name = self.var_decl()?;
+                    self.expect(&Tkt::Colon)?;
+
+                    let (ids, pat) = self.pattern()?;
+                    identifiers.extend(ids);
+                    fields.push((name, pat));
+
+                    if self.current.token != Tkt::Rbrace {
+                        self.expect_and_skip(&Tkt::Comma)?;
+                    }
+                }
+
+                self.expect(&Tkt::Rbrace)?;
+
+                return Ok((identifiers, Pattern::Struct(fields)));
+            }
+            _ => self.unexpected("pattern")?,
         };
 
         self.next()?;
@@ -508,11 +737,29 @@ impl Parser {
 
         self.expect(&Tkt::Let)?;
 
-        let (ids, bind) = self.pattern()?;
+        let mut ids = vec![];
+        let mut names = HashSet::new();
+        let mut binds = vec![];
+        loop {
+            let (bind_ids, bind) = self.pattern()?;
+            // the bindings are simultaneous, so one name can't be bound twice
+            if let Some(name) = bind.names().into_iter().find(|name| !names.insert(*name)) {
+                self.throw(ParseErrorKind::ShadowedName(name.as_str().to_string()))?;
+            }
+            let ty = self.annotation()?;
 
-        self.expect(&Tkt::Assign)?;
+            self.expect(&Tkt::Assign)?;
 
-        let value = self.expr()?;
+            let value = self.expr()?;
+
+            ids.extend(bind_ids);
+            binds.push((bind, ty, value));
+
+            if self.current.token != Tkt::LetAnd {
+                break;
+            }
+            self.next()?;
+        }
 
         self.expect(&Tkt::In)?;
 
@@ -524,8 +771,7 @@ impl Parser {
 
         Ok(Expr::new(
             ExprKind::Let {
-                bind,
-                value: Box::new(value),
+                binds,
                 body: Box::new(body),
             },
             line,
@@ -684,12 +930,12 @@ impl Parser {
     }
 
     fn cmp(&mut self) -> ParseResult<Expr> {
-        let mut left = self.cons()?;
+        let mut left = self.range()?;
 
         while let Tkt::Less | Tkt::LessEq | Tkt::Greater | Tkt::GreaterEq = self.current.token {
             let op = self.current.clone();
             self.next()?;
-            let right = self.cons()?;
+            let right = self.range()?;
 
             left = Expr::new(
                 ExprKind::Binary {
@@ -705,6 +951,40 @@ impl Parser {
         Ok(left)
     }
 
+    /// `start..end` desugars to `range start end`, and `start..=end` to `range start (end + 1)`
+    fn range(&mut self) -> ParseResult<Expr> {
+        let start = self.cons()?;
+
+        let inclusive = match self.current.token {
+            Tkt::DotDot => false,
+            Tkt::DotDotEq => true,
+            _ => return Ok(start),
+        };
+        let loc = Location {
+            line: self.current.line,
+            column: self.current.column,
+        };
+        self.next()?;
+
+        let mut end = self.cons()?;
+        if inclusive {
+            let one = Expr::new(ExprKind::Lit(Literal::Num(1.0)), loc.line, loc.column);
+            let add = ExprKind::Binary {
+                left: Box::new(end),
+                op: ast::BinOp::Add,
+                right: Box::new(one),
+            };
+            end = Expr::new(add, loc.line, loc.column);
+        }
+
+        let range = Expr::new(
+            ExprKind::Global(Symbol::from("range")),
+            loc.line,
+            loc.column,
+        );
+        Ok(app(range, vec![start, end], loc))
+    }
+
     fn cons(&mut self) -> ParseResult<Expr> {
         let mut left = self.bitwise()?;
 
@@ -794,7 +1074,7 @@ impl Parser {
     }
 
     fn prefix(&mut self) -> ParseResult<Expr> {
-        if let Tkt::Sub | Tkt::Not = &self.current.token {
+        if let Tkt::Sub | Tkt::Not | Tkt::Len = &self.current.token {
             let op = self.current.clone();
             self.next()?;
             let right = self.prefix()?;
@@ -817,7 +1097,12 @@ impl Parser {
         let mut last_state = self.state();
         let mut args = vec![];
 
-        while let Ok(arg) = self.method_ref() {
+        // a statement ends the arguments, otherwise every global would try to parse the rest of the
+        // file as its arguments
+        while !self.starts_statement() {
+            let Ok(arg) = self.method_ref() else {
+                break;
+            };
             args.push(arg);
             last_state = self.state();
         }
@@ -838,6 +1123,17 @@ impl Parser {
         }
     }
 
+    /// Checks if the current token starts a global statement, which ends the expression before it
+    fn starts_statement(&self) -> bool {
+        let position = (self.current.line, self.current.column);
+
+        match self.current.token {
+            Tkt::Let | Tkt::Def => !self.expr_bindings.contains(&position),
+            Tkt::Type => true,
+            _ => false,
+        }
+    }
+
     fn method_ref(&mut self) -> ParseResult<Expr> {
         let mut ty = self.primary()?;
 
@@ -868,6 +1164,13 @@ impl Parser {
         while self.current.token != Tkt::Rbrack {
             exprs.push(self.expr()?); // compiles the argument
 
+            if exprs.len() == 1 && self.current.token == Tkt::For {
+                let elem = exprs.pop().unwrap();
+                let list = self.comprehension(elem, line, column)?;
+                self.expect(&Tkt::Rbrack)?;
+                return Ok(list);
+            }
+
             if self.current.token != Tkt::Rbrack {
                 self.expect_and_skip(&Tkt::Comma)?;
             }
@@ -878,6 +1181,31 @@ impl Parser {
         Ok(Expr::new(ExprKind::List(exprs), line, column))
     }
 
+    /// Parses the `for x in xs` generators and the `if` filter of a list comprehension
+    fn comprehension(&mut self, elem: Expr, line: usize, column: usize) -> ParseResult<Expr> {
+        let mut generators = vec![];
+        while self.current.token == Tkt::For {
+            self.next()?;
+            let (_, pat) = self.pattern()?;
+            self.expect(&Tkt::In)?;
+            generators.push((pat, self.expr()?));
+        }
+
+        let filter = if self.current.token == Tkt::If {
+            self.next()?;
+            Some(Box::new(self.expr()?))
+        } else {
+            None
+        };
+
+        let comprehension = ExprKind::Comprehension {
+            elem: Box::new(elem),
+            generators,
+            filter,
+        };
+        Ok(Expr::new(comprehension, line, column))
+    }
+
     fn tuple(&mut self) -> ParseResult<Expr> {
         let line = self.current.line;
         let column = self.current.column;
@@ -903,6 +1231,93 @@ impl Parser {
         }
     }
 
+    fn struct_(&mut self) -> ParseResult<Expr> {
+        let line = self.current.line;
+        let column = self.current.column;
+
+        let mut fields = Vec::new();
+
+        self.expect(&Tkt::Percent)?;
+
+        // `%{base with x: 1}` unless it starts like a field, `name:`
+        let base = match self.current.token.clone() {
+            Tkt::Rbrace => None,
+            Tkt::Name(_) if self.peek()?.token == Tkt::Colon => None,
+            _ => {
+                let base = self.expr()?;
+                self.expect(&Tkt::With)?;
+                Some(base)
+            }
+        };
+
+        while self.current.token != Tkt::Rbrace {
+            let name = self.var_decl()?;
+            self.expect(&Tkt::Colon)?;
+            fields.push((name, self.expr()?));
+
+            if self.current.token != Tkt::Rbrace {
+                self.expect_and_skip(&Tkt::Comma)?;
+            }
+        }
+
+        self.expect(&Tkt::Rbrace)?;
+
+        let kind = match base {
+            Some(base) => ExprKind::StructUpdate {
+                base: Box::new(base),
+                fields,
+            },
+            None => ExprKind::Struct(fields),
+        };
+        Ok(Expr::new(kind, line, column))
+    }
+
+    /// Desugars an interpolated string into concatenations, `"a ${x}"` becomes `"a " + show x`
+    fn template(&self, parts: Vec<TemplatePart>, line: usize, column: usize) -> ParseResult<Expr> {
+        let mut template: Option<Expr> = None;
+
+        for part in parts {
+            let expr = match part {
+                TemplatePart::Str(s) if s.is_empty() => continue,
+                TemplatePart::Str(s) => Expr::new(ExprKind::Lit(Literal::Str(s)), line, column),
+                TemplatePart::Expr { src, line, column } => {
+                    let mut lexer = Lexer::new(src);
+                    lexer.set_state((line, column, 0));
+
+                    let mut parser = Parser::new(lexer)?;
+                    parser.locals.clone_from(&self.locals);
+                    let expr = parser.expr()?;
+                    if parser.current.token != Tkt::Eof {
+                        parser.unexpected("}")?;
+                    }
+
+                    let show = Expr::new(ExprKind::Global(Symbol::from("show")), line, column);
+                    let app = ExprKind::App {
+                        callee: Box::new(show),
+                        args: vec![expr],
+                        tail: false,
+                    };
+                    Expr::new(app, line, column)
+                }
+            };
+
+            template = Some(match template {
+                None => expr,
+                Some(left) => {
+                    let concat = ExprKind::Binary {
+                        left: Box::new(left),
+                        op: ast::BinOp::Add,
+                        right: Box::new(expr),
+                    };
+                    Expr::new(concat, line, column)
+                }
+            });
+        }
+
+        Ok(template
+            .unwrap_or_else(|| Expr::new(ExprKind::Lit(Literal::Str(String::new())), line, column)))
+    }
+
     fn primary(&mut self) -> ParseResult<Expr> {
         let line = self.current.line;
         let column = self.current.column;
@@ -917,6 +1332,10 @@ impl Parser {
                 self.next()?;
                 Expr::new(ExprKind::Lit(Literal::Str(s)), line, column)
             }
+            Tkt::Template(parts) => {
+                self.next()?;
+                self.template(parts, line, column)?
+            }
             Tkt::True => {
                 self.next()?;
                 Expr::new(ExprKind::Lit(Literal::Bool(true)), line, column)
@@ -935,6 +1354,7 @@ impl Parser {
             }
             Tkt::Lbrack => self.list()?,
             Tkt::Lparen => self.tuple()?,
+            Tkt::Percent => self.struct_()?,
             Tkt::Nil => {
                 self.next()?;
                 Expr::new(ExprKind::Lit(Literal::Unit), line, column)
@@ -950,13 +1370,22 @@ impl Parser {
             Tkt::Try => self.try_()?,
 
             // not supported
-            other => self.throw(format!("unexpected token '{}'", other))?,
+            _ => self.unexpected_one_of(EXPR_START)?,
         };
 
         Ok(obj)
     }
 }
 
+fn app(callee: Expr, args: Vec<Expr>, loc: Location) -> Expr {
+    let app = ExprKind::App {
+        callee: Box::new(callee),
+        args,
+        tail: false,
+    };
+    Expr::new(app, loc.line, loc.column)
+}
+
 #[test]
 fn parse_fn() {
     crate::parse_expr("fn arg -> 10").unwrap();
@@ -977,6 +1406,22 @@ fn parse_local_let() {
     assert!(crate::parse_expr("let x = 10 in x").is_ok());
 }
 
+#[test]
+fn parse_let_and() {
+    assert!(crate::parse_expr("let x = 1 and y = 2 in x + y").is_ok());
+    assert!(crate::parse_expr("let x = 1 and (y, z): (Num, Num) = (2, 3) and w = 4 in x").is_ok());
+    assert!(crate::parse_expr("let x = 1 and in x").is_err());
+
+    for src in [
+        "let x = 1 and x = 2 in x",
+        "let (x, y) = (1, 2) and x :: _ = [3] in x",
+    ] {
+        let err = crate::parse_expr(src).unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::ShadowedName("x".to_string()));
+    }
+    assert!(crate::parse_expr("let _ = 1 and _ = 2 in 3").is_ok());
+}
+
 #[test]
 fn parse_tuple() {
     assert!(crate::parse_expr("(1, 2, 3)").is_ok());
@@ -1002,7 +1447,193 @@ fn parse_codition_and() {
     assert!(crate::parse_expr("2 && 2").is_ok())
 }
 
+#[test]
+fn parse_elif() {
+    assert!(crate::parse_expr("if 1 then 2 elif 3 then 4 else 5").is_ok());
+    assert!(crate::parse_expr("if 1 then 2 elif 3 then 4").is_ok());
+    assert!(crate::parse_expr("if 1 then 2 elif 3").is_err());
+}
+
+#[test]
+fn parse_if_without_else() {
+    let (bytecode, _) = crate::parse_expr("if 1 then 2").unwrap();
+    let (with_nil, _) = crate::parse_expr("if 1 then 2 else nil").unwrap();
+
+    let opcodes = |bytecode: vm::Bytecode| bytecode.iter().map(|op| op.opcode).collect::<Vec<_>>();
+    assert_eq!(opcodes(bytecode), opcodes(with_nil));
+}
+
 #[test]
 fn parse_list() {
     assert!(crate::parse_expr("[1, 2, 3]").is_ok());
 }
+
+#[test]
+fn parse_truncated_input_is_eof() {
+    for src in [
+        "fn arg -> ",
+        "let x = 10 in",
+        "(1, 2",
+        "match x with | 1 ->",
+    ] {
+        let err = crate::parse_expr(src).unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::UnexpectedEof, "{src}");
+    }
+
+    let err = crate::parse("def c a b =").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn parse_unexpected_token() {
+    let err = crate::parse_expr("let x = 10; x").unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ParseErrorKind::UnexpectedToken { expected, found } if expected == "in" && found == ";"
+    ));
+}
+
+#[test]
+fn parse_expected_one_of() {
+    let err = crate::parse_expr("1 + )").unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 5));
+
+    match err.kind() {
+        ParseErrorKind::ExpectedOneOf { expected, found } => {
+            assert_eq!(found, ")");
+            assert!(expected.iter().any(|tk| tk == "if"));
+            assert!(expected.iter().any(|tk| tk == "["));
+            for prefix in ["-", "!", "#"] {
+                assert!(expected.iter().any(|tk| tk == prefix));
+            }
+        }
+        other => panic!("unexpected error kind {other:?}"),
+    }
+}
+
+#[test]
+fn parse_recovering_skips_bad_statement() {
+    let lexer = crate::lexer::Lexer::new("def f = 1\ndef g x = x\nlet y = ) \nlet z = 2");
+    let (stmts, errors) = Parser::new(lexer).unwrap().parse_recovering();
+
+    assert_eq!(stmts.len(), 2);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].line(), 1);
+    assert_eq!(errors[1].line(), 3);
+}
+
+#[test]
+fn parse_globals_not_taken_as_arguments() {
+    let src = "let a = f x\n".repeat(2000);
+    let stmts = Parser::new(crate::lexer::Lexer::new(src))
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(stmts.len(), 2000);
+}
+
+#[test]
+fn parse_let_in_as_argument() {
+    let src = "let a = println let x = 1 in x\nlet b = f def g y = y in g";
+    let stmts = Parser::new(crate::lexer::Lexer::new(src))
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(stmts.len(), 2);
+
+    // the `in` of a comprehension doesn't close a `def`
+    let src = "def g xs = [x for x in xs]\nlet y = 1 in y";
+    let closed = bindings_with_in(crate::lexer::Lexer::new(src));
+    assert_eq!(closed.len(), 1);
+    assert!(closed.iter().all(|(line, _)| *line == 2));
+}
+
+#[test]
+fn parse_annotations() {
+    for src in [
+        "let x: Num = 1",
+        "let (a, b): (Num, Str) = (1, \"b\")",
+        "def f (x: Num) (y: [Num]): Num = x",
+        "def g (f: Num -> Num) x = f x",
+        "let h = fn (x: Num): Num -> x + 1",
+        "def k x: ((Num -> Num) -> Str) = fn y -> \"\"",
+    ] {
+        crate::parse(src).unwrap_or_else(|e| panic!("{src}: {e}"));
+    }
+
+    assert!(crate::parse_expr("let x: = 1 in x").is_err());
+}
+
+#[test]
+fn parse_string_interpolation() {
+    let opcodes = |src| {
+        let (bytecode, constants) = crate::parse_expr(src).unwrap();
+        let opcodes: Vec<_> = bytecode.iter().map(|op| op.opcode).collect();
+        (opcodes, constants)
+    };
+
+    assert_eq!(
+        opcodes(r#""a ${1 + 1} b""#),
+        opcodes(r#""a " + show (1 + 1) + " b""#)
+    );
+    assert_eq!(opcodes(r#""${ %{a: "}"} }""#), opcodes(r#"show %{a: "}"}"#));
+    assert_eq!(
+        opcodes(r#""\${x}""#).1,
+        [vm::Value::from("${x}".to_string())]
+    );
+}
+
+#[test]
+fn parse_string_interpolation_errors() {
+    let err = crate::parse_expr("\"a ${1 +\"").unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::UnclosedDelimiter);
+
+    let err = crate::parse_expr("\"a\n ${1 )}\"").unwrap_err();
+    assert_eq!(err.line(), 2);
+}
+
+#[test]
+fn parse_list_comprehension() {
+    let opcodes = |src| {
+        let (bytecode, _) = crate::parse_expr(src).unwrap();
+        bytecode.iter().map(|op| op.opcode).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        opcodes("[x * 2 for x in xs]"),
+        opcodes("List.map (fn x -> x * 2) xs")
+    );
+    assert_eq!(
+        opcodes("[x for x in xs if x > 0]"),
+        opcodes("List.map (fn x -> x) (List.filter (fn x -> x > 0) xs)")
+    );
+    assert_eq!(
+        opcodes("[(x, y) for (x, _) in xs for y in ys]"),
+        opcodes(
+            "List.flattenDepth 1 (List.map (fn (x, _) -> List.map ((fn x y -> (x, y)) x) ys) xs)"
+        )
+    );
+    assert!(crate::parse_expr("[x for x in]").is_err());
+    assert!(crate::parse_expr("[x, y for x in xs]").is_err());
+}
+
+#[test]
+fn parse_struct_update() {
+    use vm::OpCode;
+
+    let (bytecode, _) = crate::parse_expr("%{p with x: 1, y: 2}").unwrap();
+    let opcodes: Vec<_> = bytecode.iter().map(|op| op.opcode).collect();
+    assert!(matches!(
+        opcodes[..],
+        [
+            OpCode::Loag(_),
+            OpCode::Push(_),
+            OpCode::Set(x),
+            OpCode::Push(_),
+            OpCode::Set(y),
+        ] if x == Symbol::from("x") && y == Symbol::from("y")
+    ));
+
+    assert!(crate::parse_expr("%{f p with x: 1}").is_ok());
+    assert!(crate::parse_expr("%{p x: 1}").is_err());
+}
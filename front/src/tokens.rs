@@ -3,6 +3,8 @@ pub enum TokenType {
     // Literals
     Num(f64),
     Str(String),
+    /// A string literal with `${...}` interpolations
+    Template(Vec<TemplatePart>),
     Sym(vm::Symbol),
     Name(vm::Symbol),
     True,
@@ -11,11 +13,15 @@ pub enum TokenType {
 
     // Keywords
     If,
+    Elif,
     Else,
     Then,
     Def,
     Let,
+    /// `and`, separates the bindings of a `let`
+    LetAnd,
     In,
+    For,
     Fn,
     Type,
     Match,
@@ -52,6 +58,9 @@ pub enum TokenType {
     Or,
     Not,
 
+    // length operator
+    Len,
+
     // Symbol
     Lparen,
     Rparen,
@@ -59,6 +68,7 @@ pub enum TokenType {
     Rbrack,
     Lbrace,
     Rbrace,
+    Percent, // `%{`, opens a struct
     Comma,
     Colon,
     Semicolon,
@@ -66,6 +76,8 @@ pub enum TokenType {
     Arrow,
     FatArrow,
     Dot,
+    DotDot,
+    DotDotEq,
     Pipe,
     Bar,
 
@@ -83,6 +95,16 @@ impl std::fmt::Display for TokenType {
         let res = match self {
             Self::Num(n) => n.to_string(),
             Self::Str(s) => "\"".to_owned() + s + "\"",
+            Self::Template(parts) => {
+                let parts: String = parts
+                    .iter()
+                    .map(|part| match part {
+                        TemplatePart::Str(s) => s.clone(),
+                        TemplatePart::Expr { src, .. } => format!("${{{src}}}"),
+                    })
+                    .collect();
+                format!("\"{parts}\"")
+            }
             Self::Sym(s) => format!(":{}", s),
             Self::Name(v) => format!("{}", v),
             Self::True => "true".to_string(),
@@ -90,11 +112,14 @@ impl std::fmt::Display for TokenType {
             Self::Nil => "nil".into(),
 
             Self::If => "if".into(),
+            Self::Elif => "elif".into(),
             Self::Else => "else".into(),
             Self::Then => "then".into(),
             Self::Def => "def".into(),
             Self::Let => "let".into(),
+            Self::LetAnd => "and".into(),
             Self::In => "in".into(),
+            Self::For => "for".into(),
             Self::Fn => "fn".into(),
             Self::Type => "type".into(),
             Self::Match => "match".into(),
@@ -118,6 +143,8 @@ impl std::fmt::Display for TokenType {
             Self::Assign => '='.into(),
             Self::Cons => "::".into(),
             Self::Dot => '.'.into(),
+            Self::DotDot => "..".into(),
+            Self::DotDotEq => "..=".into(),
 
             Self::BitAnd => "&&&".into(),
             Self::BitOr => "|||".into(),
@@ -129,12 +156,15 @@ impl std::fmt::Display for TokenType {
             Self::Or => "||".into(),
             Self::Not => "!".into(),
 
+            Self::Len => '#'.into(),
+
             Self::Lparen => '('.into(),
             Self::Rparen => ')'.into(),
             Self::Lbrack => '['.into(),
             Self::Rbrack => ']'.into(),
             Self::Lbrace => '{'.into(),
             Self::Rbrace => '}'.into(),
+            Self::Percent => "%{".into(),
             Self::Comma => ','.into(),
             Self::Colon => ':'.into(),
             Self::Semicolon => ';'.into(),
@@ -154,11 +184,14 @@ impl std::fmt::Display for TokenType {
 pub fn fetch_keyword<T: AsRef<str>>(word: T) -> Option<TokenType> {
     match word.as_ref() {
         "if" => Some(TokenType::If),
+        "elif" => Some(TokenType::Elif),
         "else" => Some(TokenType::Else),
         "then" => Some(TokenType::Then),
         "def" => Some(TokenType::Def),
         "let" => Some(TokenType::Let),
+        "and" => Some(TokenType::LetAnd),
         "in" => Some(TokenType::In),
+        "for" => Some(TokenType::For),
         "true" => Some(TokenType::True),
         "false" => Some(TokenType::False),
         "nil" => Some(TokenType::Nil),
@@ -174,6 +207,18 @@ pub fn fetch_keyword<T: AsRef<str>>(word: T) -> Option<TokenType> {
     }
 }
 
+/// A piece of an interpolated string literal
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplatePart {
+    Str(String),
+    /// The source of a `${...}` expression, the parser lexes it starting at `line` and `column`
+    Expr {
+        src: String,
+        line: usize,
+        column: usize,
+    },
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
     pub line: usize,
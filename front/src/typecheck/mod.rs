@@ -0,0 +1,545 @@
+use vm::Symbol;
+
+use crate::parser::ast::{
+    self, BinOp, Def, Expr, ExprKind, Literal, Location, MatchArm, Pattern, Rescue, Stmt, StmtKind,
+    UnOp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Num,
+    Str,
+    Bool,
+    Sym,
+    Unit,
+    List,
+    Tuple,
+    Fn,
+    Struct,
+    /// Anything the checker can't infer, never reported as an error
+    Unknown,
+}
+
+impl From<&ast::Type> for Type {
+    fn from(ty: &ast::Type) -> Self {
+        match ty {
+            ast::Type::Name(path) if path.len() == 1 => match path[0].as_str() {
+                "Num" => Self::Num,
+                "Str" => Self::Str,
+                "Bool" => Self::Bool,
+                "Sym" => Self::Sym,
+                "Nil" => Self::Unit,
+                "List" => Self::List,
+                "Tuple" => Self::Tuple,
+                "Fn" => Self::Fn,
+                "Struct" => Self::Struct,
+                _ => Self::Unknown,
+            },
+            ast::Type::Name(_) => Self::Unknown,
+            ast::Type::List(_) => Self::List,
+            ast::Type::Tuple(_) => Self::Tuple,
+            ast::Type::Fn(..) => Self::Fn,
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Num => "Num",
+            Self::Str => "Str",
+            Self::Bool => "Bool",
+            Self::Sym => "Sym",
+            Self::Unit => "Nil",
+            Self::List => "List",
+            Self::Tuple => "Tuple",
+            Self::Fn => "Fn",
+            Self::Struct => "Struct",
+            Self::Unknown => "?",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// Type errors found before compiling
+pub struct TypeError {
+    line: usize,
+    column: usize,
+    kind: TypeErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The kind of a [`TypeError`]
+pub enum TypeErrorKind {
+    /// A binary operator applied to operands it can't work with together
+    InvalidOperands {
+        /// The operator
+        op: String,
+        /// Type of the left operand
+        left: String,
+        /// Type of the right operand
+        right: String,
+    },
+    /// The branches of an `if` have different types
+    BranchMismatch {
+        /// Type of the `then` branch
+        then: String,
+        /// Type of the `else` branch
+        else_: String,
+    },
+    /// A value that doesn't match its type annotation
+    AnnotationMismatch {
+        /// The annotated type
+        expected: String,
+        /// The type of the value
+        found: String,
+    },
+}
+
+impl std::fmt::Display for TypeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOperands { op, left, right } => {
+                write!(f, "Cannot apply '{op}' between '{left}' and '{right}'")
+            }
+            Self::BranchMismatch { then, else_ } => {
+                write!(
+                    f,
+                    "The branches of 'if' have different types, '{then}' and '{else_}'"
+                )
+            }
+            Self::AnnotationMismatch { expected, found } => {
+                write!(f, "Expected a value of type '{expected}', found '{found}'")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}:{}] {}", self.line, self.column, self.kind)
+    }
+}
+
+impl TypeError {
+    /// The line where the error happened
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column where the error happened
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// What went wrong
+    #[must_use]
+    pub fn kind(&self) -> &TypeErrorKind {
+        &self.kind
+    }
+}
+
+fn op_str(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Less => "<",
+        BinOp::LessEq => "<=",
+        BinOp::Greater => ">",
+        BinOp::GreaterEq => ">=",
+        BinOp::Add => "+",
+        BinOp::Sub => "-",
+        BinOp::Mul => "*",
+        BinOp::Div => "/",
+        BinOp::Rem => "%",
+        BinOp::BitAnd => "&&&",
+        BinOp::BitOr => "|||",
+        BinOp::BitXor => "^^^",
+        BinOp::Shr => ">>>",
+        BinOp::Shl => "<<<",
+        BinOp::Eq => "==",
+        BinOp::Ne => "!=",
+        BinOp::And => "&&",
+        BinOp::Or => "||",
+        BinOp::Is => "is",
+    }
+}
+
+/// Infers what it can about the types of the program, only literals, operators, `if`
+/// branches and annotations are checked, everything else is left unknown
+#[derive(Default)]
+pub struct TypeChecker {
+    errors: Vec<TypeError>,
+    locals: Vec<(Symbol, Type)>,
+}
+
+impl TypeChecker {
+    /// Checks every statement, returning all the errors found
+    pub fn check(mut self, stmts: &[Stmt]) -> Vec<TypeError> {
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::Def(Def { value, .. }) => {
+                    self.expr(value);
+                }
+                StmtKind::Let { ty, value, .. } => {
+                    let found = self.expr(value);
+                    self.annotated(ty.as_ref(), found, value.location);
+                }
+                StmtKind::Type { members, .. } => {
+                    for Def { value, .. } in members {
+                        self.expr(value);
+                    }
+                }
+            }
+        }
+
+        self.errors
+    }
+
+    fn error(&mut self, loc: Location, kind: TypeErrorKind) {
+        self.errors.push(TypeError {
+            line: loc.line,
+            column: loc.column,
+            kind,
+        });
+    }
+
+    /// Checks `found` against the annotation, returning the most precise of both types
+    fn annotated(&mut self, ty: Option<&ast::Type>, found: Type, loc: Location) -> Type {
+        let Some(ty) = ty else {
+            return found;
+        };
+
+        match (Type::from(ty), found) {
+            (Type::Unknown, found) => found,
+            (expected, Type::Unknown) => expected,
+            (expected, found) if expected == found => found,
+            (expected, found) => {
+                let kind = TypeErrorKind::AnnotationMismatch {
+                    expected: ty.to_string(),
+                    found: found.to_string(),
+                };
+                self.error(loc, kind);
+                expected
+            }
+        }
+    }
+
+    fn binary(&mut self, left: Type, op: BinOp, right: Type, loc: Location) -> Type {
+        let known = left != Type::Unknown && right != Type::Unknown;
+
+        match op {
+            BinOp::Eq | BinOp::Ne | BinOp::Is => Type::Bool,
+            BinOp::And | BinOp::Or => {
+                if left == right {
+                    left
+                } else {
+                    Type::Unknown
+                }
+            }
+            BinOp::Less | BinOp::LessEq | BinOp::Greater | BinOp::GreaterEq => {
+                if known && left != right {
+                    self.invalid_operands(left, op, right, loc);
+                }
+                Type::Bool
+            }
+            _ => match (left, right) {
+                (Type::Num, Type::Num) => Type::Num,
+                (Type::Str, Type::Str) => Type::Str,
+                _ if known => {
+                    self.invalid_operands(left, op, right, loc);
+                    Type::Unknown
+                }
+                _ => Type::Unknown,
+            },
+        }
+    }
+
+    fn invalid_operands(&mut self, left: Type, op: BinOp, right: Type, loc: Location) {
+        let kind = TypeErrorKind::InvalidOperands {
+            op: op_str(op).to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+        };
+        self.error(loc, kind);
+    }
+
+    fn arms(&mut self, arms: &[MatchArm]) {
+        for arm in arms {
+            if let Some(guard) = &arm.guard {
+                self.expr(guard);
+            }
+            self.expr(&arm.body);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Type {
+        match &expr.kind {
+            ExprKind::Lit(lit) => match lit {
+                Literal::Num(_) => Type::Num,
+                Literal::Str(_) => Type::Str,
+                Literal::Bool(_) => Type::Bool,
+                Literal::Sym(_) => Type::Sym,
+                Literal::Unit => Type::Unit,
+            },
+
+            ExprKind::Binary { left, op, right } => {
+                let left = self.expr(left);
+                let right = self.expr(right);
+                self.binary(left, *op, right, expr.location)
+            }
+
+            ExprKind::UnOp(op, value) => {
+                let value = self.expr(value);
+                match op {
+                    UnOp::Not => Type::Bool,
+                    UnOp::Neg if value == Type::Num => Type::Num,
+                    UnOp::Neg => Type::Unknown,
+                    UnOp::Len => Type::Num,
+                }
+            }
+
+            ExprKind::If { cond, then, else_ } => {
+                self.expr(cond);
+                let then = self.expr(then);
+                // a missing `else` is `nil`
+                let else_ = else_.as_ref().map_or(Type::Unit, |else_| self.expr(else_));
+
+                match (then, else_) {
+                    (Type::Unknown, _) | (_, Type::Unknown) => Type::Unknown,
+                    (then, else_) if then == else_ => then,
+                    // a `nil` branch makes the value optional, which isn't a mismatch
+                    (Type::Unit, _) | (_, Type::Unit) => Type::Unknown,
+                    (then, else_) => {
+                        let kind = TypeErrorKind::BranchMismatch {
+                            then: then.to_string(),
+                            else_: else_.to_string(),
+                        };
+                        self.error(expr.location, kind);
+                        Type::Unknown
+                    }
+                }
+            }
+
+            ExprKind::Let { binds, body } => {
+                let mut tys = vec![];
+                for (_, ty, value) in binds {
+                    let found = self.expr(value);
+                    tys.push(self.annotated(ty.as_ref(), found, value.location));
+                }
+
+                let len = self.locals.len();
+                for ((bind, _, _), ty) in binds.iter().zip(tys) {
+                    if let Pattern::Id(name) = bind {
+                        self.locals.push((*name, ty));
+                    }
+                }
+
+                let body = self.expr(body);
+                self.locals.truncate(len);
+                body
+            }
+
+            ExprKind::Def { bind, body } => {
+                self.expr(&bind.value);
+                self.expr(body)
+            }
+
+            ExprKind::Match { expr, arms } => {
+                self.expr(expr);
+                self.arms(arms);
+                Type::Unknown
+            }
+
+            ExprKind::Lambda {
+                args,
+                arg_types,
+                ret,
+                body,
+            } => {
+                // lambdas don't capture, so only the arguments are in scope
+                let outer = std::mem::take(&mut self.locals);
+                for (arg, ty) in args.iter().zip(arg_types) {
+                    if let (Pattern::Id(name), Some(ty)) = (arg, ty) {
+                        self.locals.push((*name, ty.into()));
+                    }
+                }
+
+                let found = self.expr(body);
+                self.annotated(ret.as_ref(), found, body.location);
+
+                self.locals = outer;
+                Type::Fn
+            }
+
+            ExprKind::App { callee, args, .. } => {
+                self.expr(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+                Type::Unknown
+            }
+
+            ExprKind::MethodRef { ty, .. } => {
+                self.expr(ty);
+                Type::Unknown
+            }
+
+            ExprKind::List(xs) => {
+                for x in xs {
+                    self.expr(x);
+                }
+                Type::List
+            }
+
+            ExprKind::Cons { head, tail } => {
+                self.expr(head);
+                self.expr(tail);
+                Type::List
+            }
+
+            ExprKind::Try { body, rescue } => {
+                self.expr(body);
+                match rescue {
+                    Rescue::Bind(_, rescue) => {
+                        self.expr(rescue);
+                    }
+                    Rescue::Arms(arms) => self.arms(arms),
+                }
+                Type::Unknown
+            }
+
+            ExprKind::Comprehension {
+                elem,
+                generators,
+                filter,
+            } => {
+                let len = self.locals.len();
+                for (pat, iter) in generators {
+                    self.expr(iter);
+                    for name in pat.names() {
+                        self.locals.push((name, Type::Unknown));
+                    }
+                }
+
+                if let Some(filter) = filter {
+                    self.expr(filter);
+                }
+                self.expr(elem);
+                self.locals.truncate(len);
+                Type::List
+            }
+
+            ExprKind::Tuple(xs) => {
+                for x in xs {
+                    self.expr(x);
+                }
+                Type::Tuple
+            }
+
+            ExprKind::Struct(fields) => {
+                for (_, value) in fields {
+                    self.expr(value);
+                }
+                Type::Struct
+            }
+
+            ExprKind::StructUpdate { base, fields } => {
+                self.expr(base);
+                for (_, value) in fields {
+                    self.expr(value);
+                }
+                Type::Struct
+            }
+
+            ExprKind::Var(name) => self
+                .locals
+                .iter()
+                .rev()
+                .find(|(local, _)| local == name)
+                .map_or(Type::Unknown, |(_, ty)| *ty),
+
+            ExprKind::Global(_) => Type::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+fn check(src: &str) -> Vec<TypeError> {
+    let lexer = crate::lexer::Lexer::new(src);
+    let stmts = crate::parser::Parser::new(lexer).unwrap().parse().unwrap();
+    TypeChecker::default().check(&stmts)
+}
+
+#[test]
+fn mismatched_operands() {
+    let errors = check("let x = 1 + \"a\"");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind(),
+        &TypeErrorKind::InvalidOperands {
+            op: "+".to_string(),
+            left: "Num".to_string(),
+            right: "Str".to_string(),
+        }
+    );
+    assert_eq!(errors[0].line(), 1);
+}
+
+#[test]
+fn mismatched_if_branches() {
+    let errors = check("def f x = if x then 1 else \"one\"");
+
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].kind(),
+        TypeErrorKind::BranchMismatch { .. }
+    ));
+}
+
+#[test]
+fn nil_branches_are_optional_values() {
+    assert!(check("def f x = if x then 1 else nil").is_empty());
+    assert!(check("def f x = if x then 1").is_empty());
+    assert!(check("def f x = if x then nil else \"one\"").is_empty());
+}
+
+#[test]
+fn unknown_types_are_not_errors() {
+    assert!(check("def f x = x + 1\nlet y = if true then f 1 else \"a\" + \"b\"").is_empty());
+}
+
+#[test]
+fn mismatched_annotations() {
+    let errors = check("let x: [Str] = 1\ndef f (x: Num): Num = x + \"a\"");
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[0].kind(),
+        &TypeErrorKind::AnnotationMismatch {
+            expected: "[Str]".to_string(),
+            found: "Num".to_string(),
+        }
+    );
+    assert!(matches!(
+        errors[1].kind(),
+        TypeErrorKind::InvalidOperands { .. }
+    ));
+}
+
+#[test]
+fn let_and_bindings_are_typed() {
+    let errors = check("let y = let a = 1 and b = \"b\" in a + b");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind(),
+        &TypeErrorKind::InvalidOperands {
+            op: "+".to_string(),
+            left: "Num".to_string(),
+            right: "Str".to_string(),
+        }
+    );
+}
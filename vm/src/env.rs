@@ -17,12 +17,13 @@ struct Entry {
     pub value: Value,
 }
 
-#[derive(Debug, Clone)]
 #[repr(C)]
 /// A table of key-value pairs
 pub struct EnvTable {
     capacity: usize,
     count: usize,
+    /// Removed entries, they keep their slot so probe chains going through them aren't cut
+    tombstones: usize,
     entries: *mut Entry,
 }
 
@@ -52,6 +53,7 @@ impl EnvTable {
         Self {
             capacity,
             count: 0,
+            tombstones: 0,
             entries,
         }
     }
@@ -82,7 +84,9 @@ impl EnvTable {
 
     /// Inserts an item in the table
     pub fn insert(&mut self, key: Symbol, value: Value) {
-        if self.count + (self.capacity / Self::BASE_VALUE) >= self.capacity {
+        // tombstones count towards the load, otherwise they could fill every empty slot and a
+        // lookup for a missing key would never stop probing
+        if self.count + self.tombstones + (self.capacity / Self::BASE_VALUE) >= self.capacity {
             let len = self.capacity * 2;
             self.realloc(len);
         }
@@ -92,6 +96,9 @@ impl EnvTable {
         unsafe {
             if !init {
                 self.count += 1;
+                if !(*entry).value.is_nil() {
+                    self.tombstones -= 1;
+                }
             }
             (*entry).key = Some(key);
             (*entry).value = value;
@@ -134,6 +141,8 @@ impl EnvTable {
 
         self.entries = entries;
         self.capacity = len;
+        // only the live entries were moved
+        self.tombstones = 0;
     }
 
     /// Indexes an item in the table
@@ -149,6 +158,24 @@ impl EnvTable {
         }
     }
 
+    /// Removes an item from the table, returning its value
+    pub fn remove(&mut self, key: &Symbol) -> Option<Value> {
+        let (entry, init) = unsafe { Self::find_entry(self.entries, self.capacity, key) };
+        if !init {
+            return None;
+        }
+
+        // an entry without a key but with a value is a tombstone, `find_entry` probes past it
+        let value = unsafe {
+            (*entry).key = None;
+            std::mem::replace(&mut (*entry).value, Value::Bool(true))
+        };
+        self.count -= 1;
+        self.tombstones += 1;
+
+        Some(value)
+    }
+
     /// Returns the table length
     #[must_use]
     pub fn len(&self) -> usize {
@@ -162,6 +189,13 @@ impl EnvTable {
         self.count == 0
     }
 
+    /// The entries of the table sorted by key, since the iteration order depends on the hashes
+    fn sorted(&self) -> Vec<(Key, Value)> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        entries
+    }
+
     /// Iterates over the table
     pub fn iter(&self) -> impl Iterator<Item = (Key, Value)> {
         unsafe {
@@ -176,7 +210,7 @@ impl EnvTable {
 impl std::fmt::Display for EnvTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{")?;
-        for (index, (key, value)) in self.iter().enumerate() {
+        for (index, (key, value)) in self.sorted().into_iter().enumerate() {
             if index == self.len() - 1 {
                 write!(f, "{key} = {value}")?;
             } else {
@@ -187,6 +221,24 @@ impl std::fmt::Display for EnvTable {
     }
 }
 
+impl std::fmt::Debug for EnvTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.sorted()).finish()
+    }
+}
+
+impl Clone for EnvTable {
+    /// Copies the entries to a new allocation, so both tables can be changed and dropped on their
+    /// own
+    fn clone(&self) -> Self {
+        let mut table = Self::with_capacity(self.capacity);
+        for (key, value) in self.iter() {
+            table.insert(key, value);
+        }
+        table
+    }
+}
+
 impl Default for EnvTable {
     fn default() -> Self {
         Self::new()
@@ -214,3 +266,71 @@ impl PartialEq for EnvTable {
             .all(|(key, value)| other.get(&key).map_or(false, |v| value == v))
     }
 }
+
+#[test]
+fn display_is_independent_of_insertion_order() {
+    let keys = ["zeta", "alpha", "mu", "beta", "omega", "gamma"];
+
+    let mut forward = EnvTable::new();
+    for (n, key) in keys.iter().enumerate() {
+        forward.insert(Symbol::new(*key), Value::Num(n as f64));
+    }
+
+    let mut backward = EnvTable::with_capacity(32);
+    for (n, key) in keys.iter().enumerate().rev() {
+        backward.insert(Symbol::new(*key), Value::Num(n as f64));
+    }
+
+    assert_eq!(forward.to_string(), backward.to_string());
+    assert_eq!(format!("{forward:?}"), format!("{backward:?}"));
+    assert!(forward.to_string().starts_with("{alpha = 1, beta = 3"));
+}
+
+#[test]
+fn remove_keeps_probe_chains() {
+    // keys that land on the same slot of an 8 entries table, so each one probes past the others
+    let mut colliding = (0..)
+        .map(|n| Symbol::new(format!("key{n}")))
+        .filter(|key| key.hash & 7 == 3);
+    let (a, b, c) = (
+        colliding.next().unwrap(),
+        colliding.next().unwrap(),
+        colliding.next().unwrap(),
+    );
+
+    let mut table = EnvTable::with_capacity(8);
+    table.insert(a, Value::Num(1.0));
+    table.insert(b, Value::Num(2.0));
+    table.insert(c, Value::Num(3.0));
+
+    assert_eq!(table.remove(&b), Some(Value::Num(2.0)));
+    assert_eq!(table.remove(&b), None);
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.get(&a), Some(Value::Num(1.0)));
+    assert_eq!(table.get(&b), None);
+    assert_eq!(table.get(&c), Some(Value::Num(3.0)));
+    assert_eq!(table.iter().count(), 2);
+
+    // the tombstone is reused
+    table.insert(b, Value::Num(4.0));
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.get(&b), Some(Value::Num(4.0)));
+    assert_eq!(table.get(&c), Some(Value::Num(3.0)));
+}
+
+#[test]
+fn removed_slots_dont_fill_the_table() {
+    let mut table = EnvTable::with_capacity(8);
+    let kept = Symbol::new("kept");
+    table.insert(kept, Value::Nil);
+
+    for n in 0..100 {
+        let key = Symbol::new(format!("tmp{n}"));
+        table.insert(key, Value::Num(f64::from(n)));
+        assert_eq!(table.remove(&key), Some(Value::Num(f64::from(n))));
+    }
+
+    assert_eq!(table.len(), 1);
+    assert_eq!(table.get(&Symbol::new("missing")), None);
+    assert_eq!(table.get(&kept), Some(Value::Nil));
+}
@@ -1,11 +1,13 @@
 use std::{fmt, io};
 
-use crate::{raise_err, Symbol};
+use crate::{raise_err, Symbol, Value};
 
 #[derive(Debug)]
 pub struct InterpretError {
     pub msg: String,
     pub err: Symbol,
+    /// The value given to `throw`, rescue blocks get it instead of the symbol and message
+    pub payload: Option<Box<Value>>,
     pub line: usize,
     pub column: usize,
 }
@@ -20,6 +22,38 @@ impl fmt::Display for InterpretError {
     }
 }
 
+impl InterpretError {
+    /// Creates an error without a position, the virtual machine fills it in with the position of
+    /// the instruction that raised it
+    #[must_use]
+    pub fn new(err: Symbol, msg: String) -> Self {
+        Self {
+            msg,
+            err,
+            payload: None,
+            line: 0,
+            column: 0,
+        }
+    }
+
+    /// Creates a `Thrown` error carrying an arbitrary value, its message is the shown value
+    #[must_use]
+    pub fn thrown(payload: Value) -> Self {
+        Self {
+            payload: Some(Box::new(payload.clone())),
+            ..raise_err!(Thrown, "{}", payload)
+        }
+    }
+
+    /// Sets the position of the error, if it doesn't have one yet
+    pub(crate) fn locate(&mut self, line: usize, column: usize) {
+        if self.line == 0 {
+            self.line = line;
+            self.column = column;
+        }
+    }
+}
+
 impl From<io::Error> for InterpretError {
     fn from(_: io::Error) -> Self {
         raise_err!(IOError, "Internal IO error")
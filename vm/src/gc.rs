@@ -1,8 +1,11 @@
-use std::{cell::Cell, fmt::Debug, ptr::NonNull};
+use std::{cell::Cell, fmt::Debug, mem::ManuallyDrop, ptr::NonNull};
 
 struct Ref<T> {
-    pub(in crate::gc) inner: T,
+    pub(in crate::gc) inner: ManuallyDrop<T>,
     pub(in crate::gc) count: Cell<usize>,
+    /// The number of `GcWeak`s, plus one shared by all the `GcRef`s, the allocation is only freed
+    /// when it reaches zero
+    pub(in crate::gc) weak: Cell<usize>,
 }
 
 pub struct GcRef<T> {
@@ -17,8 +20,9 @@ impl<T> GcRef<T> {
         unsafe {
             Self {
                 inner: NonNull::new_unchecked(Box::into_raw(Box::new(Ref {
-                    inner: constant,
+                    inner: ManuallyDrop::new(constant),
                     count: Cell::new(1),
+                    weak: Cell::new(1),
                 }))),
             }
         }
@@ -46,9 +50,68 @@ impl<T> GcRef<T> {
         }
     }
 
-    fn ref_count(&self) -> usize {
+    /// The number of `GcRef`s pointing to the value, `GcWeak`s aren't counted. Reference counting
+    /// can't free cycles, so a count that never drops is the first thing to look at for a leak
+    #[must_use]
+    pub fn ref_count(&self) -> usize {
         unsafe { self.inner.as_ref().count.get() }
     }
+
+    /// Creates a `GcWeak` to the value, which doesn't keep it alive
+    #[must_use]
+    pub fn downgrade(&self) -> GcWeak<T> {
+        unsafe {
+            let weak = &self.inner.as_ref().weak;
+            weak.set(weak.get() + 1);
+        }
+        GcWeak { inner: self.inner }
+    }
+}
+
+/// A reference that doesn't keep the value alive, so a value can point back to its owner without
+/// making a cycle that reference counting would never free
+pub struct GcWeak<T> {
+    inner: NonNull<Ref<T>>,
+}
+
+impl<T> GcWeak<T> {
+    /// Returns a `GcRef` to the value, or `None` if it was already dropped
+    #[must_use]
+    pub fn upgrade(&self) -> Option<GcRef<T>> {
+        let count = unsafe { &self.inner.as_ref().count };
+        if count.get() == 0 {
+            return None;
+        }
+
+        count.set(count.get() + 1);
+        Some(GcRef::from_inner(self.inner))
+    }
+}
+
+impl<T> Clone for GcWeak<T> {
+    fn clone(&self) -> Self {
+        unsafe {
+            let weak = &self.inner.as_ref().weak;
+            weak.set(weak.get() + 1);
+        }
+        Self { inner: self.inner }
+    }
+}
+
+impl<T> Drop for GcWeak<T> {
+    fn drop(&mut self) {
+        unsafe { release_weak(self.inner) };
+    }
+}
+
+/// Drops a weak count, freeing the allocation if it was the last one
+unsafe fn release_weak<T>(inner: NonNull<Ref<T>>) {
+    let weak = &inner.as_ref().weak;
+    weak.set(weak.get() - 1);
+
+    if weak.get() == 0 {
+        drop(Box::from_raw(inner.as_ptr()));
+    }
 }
 
 impl<T> Clone for GcRef<T> {
@@ -73,7 +136,12 @@ impl<T> Drop for GcRef<T> {
         self.dec_ref();
 
         if self.ref_count() == 0 {
-            unsafe { drop(Box::from_raw(self.inner.as_ptr())) };
+            // the `GcRef`s' weak count keeps the allocation alive while the value drops, in case
+            // the value holds the last `GcWeak` to itself
+            unsafe {
+                ManuallyDrop::drop(&mut (*self.inner.as_ptr()).inner);
+                release_weak(self.inner);
+            }
         }
     }
 }
@@ -91,3 +159,54 @@ impl<T: Debug> Debug for GcRef<T> {
         write!(f, "{:#?}", **self)
     }
 }
+
+#[cfg(test)]
+/// Counts how many times it was dropped
+struct Tracked<'a>(&'a Cell<usize>);
+
+#[cfg(test)]
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn weak_refs_upgrade_while_the_value_lives() {
+    let drops = Cell::new(0);
+    let strong = GcRef::new(Tracked(&drops));
+    let weak = strong.downgrade();
+
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(strong.ref_count(), 2);
+    drop(upgraded);
+    assert_eq!(strong.ref_count(), 1);
+
+    drop(strong);
+    assert_eq!(drops.get(), 1);
+    assert!(weak.upgrade().is_none());
+    assert!(weak.clone().upgrade().is_none());
+}
+
+#[test]
+fn weak_back_references_dont_leak() {
+    struct Node<'a> {
+        _tracked: Tracked<'a>,
+        parent: Option<GcWeak<Node<'a>>>,
+    }
+
+    let drops = Cell::new(0);
+    let mut node = GcRef::new(Node {
+        _tracked: Tracked(&drops),
+        parent: None,
+    });
+    // a strong reference to itself would keep the count above zero forever
+    unsafe { node.mut_ref().parent = Some(node.downgrade()) };
+
+    let parent = node.parent.as_ref().and_then(GcWeak::upgrade).unwrap();
+    assert_eq!(parent.ref_count(), 2);
+    drop(parent);
+
+    drop(node);
+    assert_eq!(drops.get(), 1);
+}
@@ -25,13 +25,16 @@ pub mod gc;
 pub mod literal;
 mod opcode;
 mod prelude;
+mod serialize;
 mod stack;
+mod validate;
 
 use gc::GcRef;
 use literal::{
     fun::{FnArgs, NativeFn},
     TryGet,
 };
+use prelude::{random::Rng, task::Task};
 
 use crate::error::InterpretResult;
 
@@ -40,21 +43,27 @@ pub use crate::{
     literal::{
         fun::{Fn, FnKind},
         list::List,
+        r#struct::Struct,
         symbol::Symbol,
         tuple::Tuple,
         yexmodule::YexModule,
         Value,
     },
     opcode::{OpCode, OpCodeMetadata},
+    serialize::{deserialize_program, serialize_program},
     stack::StackVec,
+    validate::validate,
 };
 
-const STACK_SIZE: usize = 512;
+const STACK_SIZE: usize = 8192;
+const MAX_LOCALS: usize = u16::MAX as usize;
+/// How many bytecode calls can be nested
+const MAX_FRAMES: usize = 4096;
+/// How much native stack callbacks from native functions can use, half of what a thread gets by
+/// default
+const NATIVE_STACK_BUDGET: usize = 1024 * 1024;
 const NIL: Value = Value::Nil;
 
-static mut LINE: usize = 1;
-static mut COLUMN: usize = 1;
-
 #[macro_export]
 #[doc(hidden)]
 macro_rules! raise {
@@ -67,15 +76,10 @@ macro_rules! raise {
 #[doc(hidden)]
 macro_rules! raise_err {
     ($error: ident, $($fmtargs:expr),*) => {
-        unsafe {
-            let msg = $crate::Symbol::new(stringify!($error));
-            $crate::error::InterpretError {
-                line: $crate::LINE,
-                column: $crate::COLUMN,
-                err: msg,
-                msg: format!($($fmtargs),*),
-            }
-        }
+        $crate::error::InterpretError::new(
+            $crate::Symbol::new(stringify!($error)),
+            format!($($fmtargs),*),
+        )
     };
 }
 
@@ -86,22 +90,93 @@ type Stack = StackVec<Value, STACK_SIZE>;
 pub type Bytecode = Vec<OpCodeMetadata>;
 
 type BytecodeRef<'a> = &'a Bytecode;
-use std::{mem::swap, ops, ptr};
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, Write},
+    mem::{self, swap},
+    ops, ptr,
+    time::Instant,
+};
+/// A copy of the global variables of a [`VirtualMachine`], made by
+/// [`VirtualMachine::snapshot_globals`]
+pub struct GlobalsSnapshot(EnvTable);
+
+/// A bytecode call in progress. Calls are kept in a list instead of recursing on the native
+/// stack, so deep recursion can't overflow it
+struct Frame {
+    /// the function being run, `None` for the bytecode passed to [`VirtualMachine::run`]
+    body: Option<GcRef<FnKind>>,
+    ip: usize,
+    try_stack: Vec<(usize, usize)>,
+    locals: usize,
+    /// position of the call the frame is waiting on, restored when the callee returns
+    line: usize,
+    column: usize,
+}
+
+impl Frame {
+    fn new(body: Option<GcRef<FnKind>>) -> Self {
+        Self {
+            body,
+            ip: 0,
+            try_stack: vec![],
+            locals: 0,
+            line: 0,
+            column: 0,
+        }
+    }
+
+    fn code<'a>(&'a self, bytecode: BytecodeRef<'a>) -> BytecodeRef<'a> {
+        match self.body.as_deref() {
+            Some(FnKind::Bytecode(body)) => body,
+            _ => bytecode,
+        }
+    }
+}
+
+/// Where the native stack currently is
+#[inline(never)]
+fn native_position() -> usize {
+    let marker = 0u8;
+    ptr::addr_of!(marker) as usize
+}
+
 /// Implements the Yex virtual machine, which runs the [`crate::OpCode`] instructions in a stack
 /// model
 pub struct VirtualMachine {
-    stack: Stack,
+    /// boxed, it's too big to be moved around on the native stack
+    stack: Box<Stack>,
     locals: Vec<Value>,
     used_locals: usize,
     /// the constants of the bytecode
     pub constants: Vec<Value>,
     globals: EnvTable,
+    tasks: VecDeque<Task>,
+    channels: Vec<VecDeque<Value>>,
+    /// position of the instruction being run, used to locate errors
+    line: usize,
+    column: usize,
+    frames: usize,
+    max_depth: usize,
+    /// address of the native stack when the outermost `run` started, to measure how much of it
+    /// native functions calling back into the VM use
+    native_base: usize,
+    /// how many instructions the current top-level `run` has executed
+    instructions: usize,
+    instruction_limit: Option<usize>,
+    /// where `print` and `println` write to
+    output: Box<dyn Write>,
+    /// where `input` reads from, stdin isn't kept locked so other threads can still read it
+    input: Option<Box<dyn BufRead>>,
+    /// baseline for `monotonic`, so its values stay small enough to be precise
+    started: Instant,
+    rng: Rng,
 }
 
 impl VirtualMachine {
     /// Reset the instruction pointer and the stack
     pub fn reset(&mut self) {
-        self.stack = stackvec![];
+        *self.stack = stackvec![];
     }
 
     /// sets the constants for execution
@@ -109,6 +184,12 @@ impl VirtualMachine {
         self.constants = constants;
     }
 
+    /// Limits how many calls can be nested before a `RecursionError` is raised.
+    /// The VM can't go past its own stack ceiling, so bigger limits still raise `StackOverflow`
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth;
+    }
+
     /// Pop's the last value on the stack
     #[must_use]
     pub fn pop_last(&self) -> &Value {
@@ -125,73 +206,168 @@ impl VirtualMachine {
         self.globals.insert(name.into(), value);
     }
 
+    /// Copies the current global variables, so the definitions made after it can be rolled back
+    /// with [`VirtualMachine::restore_globals`]
+    #[must_use]
+    pub fn snapshot_globals(&self) -> GlobalsSnapshot {
+        GlobalsSnapshot(self.globals.clone())
+    }
+
+    /// Restores the global variables to a snapshot, dropping everything defined after it
+    pub fn restore_globals(&mut self, snapshot: GlobalsSnapshot) {
+        self.globals = snapshot.0;
+    }
+
+    /// Redirects the output of `print` and `println`, which goes to stdout by default
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.output = Box::new(output);
+    }
+
+    /// Changes where `input` reads lines from, which is stdin by default
+    pub fn set_input(&mut self, input: impl BufRead + 'static) {
+        self.input = Some(Box::new(input));
+    }
+
+    /// Limits how many instructions each call to [`VirtualMachine::run`] can execute, including
+    /// the ones run by the functions it calls. Going past it raises a `LimitExceeded` error, which
+    /// can't be rescued by the script
+    pub fn set_instruction_limit(&mut self, limit: usize) {
+        self.instruction_limit = Some(limit);
+    }
+
     /// Executes a given set of bytecode instructions
     pub fn run(&mut self, bytecode: BytecodeRef) -> InterpretResult<()> {
-        let bytecode = bytecode;
-        let mut try_stack = vec![];
-
-        let mut ip = 0;
-        let mut frame_locals = 0;
-
-        while ip < bytecode.len() {
-            let op = unsafe {
-                let op = bytecode[ip];
-                LINE = op.line;
-                COLUMN = op.column;
-                op.opcode
+        if self.frames == 0 {
+            self.instructions = 0;
+            self.native_base = native_position();
+        }
+
+        let mut frame = Frame::new(None);
+        let mut callers = vec![];
+        let res = self.run_frames(bytecode, &mut frame, &mut callers);
+
+        // the frames are released even if it raised, otherwise rescuing an error would leak their
+        // locals
+        while self.return_to_caller(&mut frame, &mut callers) {}
+        self.used_locals -= frame.locals;
+
+        res
+    }
+
+    fn run_frames(
+        &mut self,
+        bytecode: BytecodeRef,
+        frame: &mut Frame,
+        callers: &mut Vec<Frame>,
+    ) -> InterpretResult<()> {
+        loop {
+            let op = match frame.code(bytecode).get(frame.ip).copied() {
+                Some(op) => op,
+                // the callee's result is left on the stack
+                None if self.return_to_caller(frame, callers) => {
+                    frame.ip += 1;
+                    continue;
+                }
+                None => return Ok(()),
             };
+            self.line = op.line;
+            self.column = op.column;
+            let op = op.opcode;
+
+            if let Some(limit) = self.instruction_limit {
+                if self.instructions >= limit {
+                    let mut e =
+                        raise_err!(LimitExceeded, "Executed more than {} instructions", limit);
+                    e.locate(self.line, self.column);
+                    return Err(e);
+                }
+                self.instructions += 1;
+            }
 
             self.debug_stack(&op);
 
             let res = match op {
                 OpCode::Try(offset) => {
-                    try_stack.push(offset);
+                    frame.try_stack.push((offset, self.stack.len()));
                     Ok(())
                 }
 
                 OpCode::EndTry => {
-                    try_stack.pop();
+                    frame.try_stack.pop();
                     Ok(())
                 }
 
                 OpCode::Jmp(offset) => {
-                    ip = offset;
+                    frame.ip = offset;
                     continue;
                 }
 
                 OpCode::Jmf(offset) => {
                     if !self.pop().to_bool() {
-                        ip = offset;
+                        frame.ip = offset;
                         continue;
                     }
                     Ok(())
                 }
 
                 OpCode::TCall(arity) => {
-                    self.valid_tail_call(arity, bytecode)?;
-                    ip = 0;
+                    self.valid_tail_call(arity, frame.code(bytecode))?;
+                    frame.ip = 0;
                     continue;
                 }
 
-                _ => self.run_op(op, &mut frame_locals),
+                // bytecode calls don't recurse, the caller waits on `callers` until the callee
+                // runs out of instructions
+                OpCode::Call(arity) => match self.enter(arity) {
+                    Ok(Some(body)) => {
+                        let caller = mem::replace(frame, Frame::new(Some(body)));
+                        callers.push(Frame {
+                            line: self.line,
+                            column: self.column,
+                            ..caller
+                        });
+                        continue;
+                    }
+                    Ok(None) => Ok(()),
+                    Err(e) => Err(e),
+                },
+
+                _ => self.run_op(op, &mut frame.locals),
             };
 
-            if let Err(e) = res {
-                if try_stack.is_empty() {
-                    return Err(e);
-                }
+            if let Err(mut e) = res {
+                e.locate(self.line, self.column);
 
-                let try_ip = try_stack.pop().unwrap();
-                self.push(e.err.into());
-                ip = try_ip;
-            }
+                // unwinds the calls until one of them rescues the error
+                let (try_ip, len) = loop {
+                    if let Some(handler) = frame.try_stack.pop() {
+                        break handler;
+                    }
 
-            ip += 1;
-        }
+                    if !self.return_to_caller(frame, callers) {
+                        return Err(e);
+                    }
+                };
 
-        self.used_locals -= frame_locals;
+                // drops what the failed expression left on the stack, so a rescued
+                // `StackOverflow` has room for the error
+                while self.stack.len() > len {
+                    self.pop();
+                }
+                // raised errors are rescued as `(symbol, message)`, thrown ones as their payload,
+                // with a flag on top telling them apart
+                let thrown = e.payload.is_some();
+                let caught = e.payload.map_or_else(
+                    || Value::Tuple(vec![e.err.into(), e.msg.into()].into()),
+                    |payload| *payload,
+                );
+                self.push(caught)?;
+                self.push(Value::Bool(thrown))?;
+                frame.ip = try_ip;
+            }
 
-        Ok(())
+            frame.ip += 1;
+        }
     }
 
     #[inline]
@@ -202,7 +378,7 @@ impl VirtualMachine {
             // Stack manipulation
             OpCode::Push(value) => {
                 let value = self.constants[value].clone();
-                self.push(value);
+                self.push(value)?;
             }
             OpCode::Pop => {
                 self.pop();
@@ -210,21 +386,21 @@ impl VirtualMachine {
 
             OpCode::Dup => {
                 let value = self.pop();
-                self.push(value.clone());
-                self.push(value);
+                self.push(value.clone())?;
+                self.push(value)?;
             }
 
-            OpCode::Swap(a, b) => unsafe {
-                let a = self.stack.get_uninit_mut(a) as *mut _;
-                let b = self.stack.get_uninit_mut(b) as *mut _;
-
-                ptr::swap(a, b);
-            },
+            OpCode::Over => {
+                let (a, b) = self.pop_two();
+                self.push(a.clone())?;
+                self.push(b)?;
+                self.push(a)?;
+            }
 
             OpCode::Rev => {
                 let (a, b) = self.pop_two();
-                self.push(b);
-                self.push(a);
+                self.push(b)?;
+                self.push(a)?;
             }
 
             OpCode::RevN(n) => {
@@ -233,13 +409,10 @@ impl VirtualMachine {
                     vec.push(self.pop());
                 }
                 for elem in vec {
-                    self.push(elem);
+                    self.push(elem)?;
                 }
             }
 
-            // function calls
-            OpCode::Call(arity) => self.call(arity)?,
-
             // mathematical operators
             OpCode::Add => self.binop(|a, b| a + b)?,
             OpCode::Sub => self.binop(|a, b| a - b)?,
@@ -258,21 +431,21 @@ impl VirtualMachine {
             OpCode::Eq => self.binop(|a, b| Ok(a == b))?,
             OpCode::Less => {
                 let (a, b) = self.pop_two();
-                self.push(a.ord_cmp(&b)?.is_lt().into());
+                self.push(a.ord_cmp(&b)?.is_lt().into())?;
             }
             OpCode::LessEq => {
                 let (a, b) = self.pop_two();
-                self.push(a.ord_cmp(&b)?.is_le().into());
+                self.push(a.ord_cmp(&b)?.is_le().into())?;
             }
 
             // unary operators
             OpCode::Not => {
                 let value = self.pop();
-                self.push(!value);
+                self.push(!value)?;
             }
             OpCode::Len => {
                 let value = self.pop();
-                self.push(Value::Num(value.len() as f64));
+                self.push(Value::Num(value.len()? as f64))?;
             }
             OpCode::Neg => {
                 let value = self.pop();
@@ -282,23 +455,25 @@ impl VirtualMachine {
             // locals manipulation
             OpCode::Load(offset) => {
                 let value = self.locals[offset + self.used_locals - *frame_locals].clone();
-                self.push(value);
+                self.push(value)?;
             }
             OpCode::Save(offset) => {
                 let value = self.pop();
+                let slot = offset + (self.used_locals - *frame_locals);
 
-                if self.used_locals + 1 >= self.locals.capacity() {
-                    let cap = self.locals.capacity();
-                    self.locals.resize(cap, NIL);
+                if slot >= self.locals.len() {
+                    let len = (slot + 1).max(self.locals.len() * 2);
+                    self.locals.resize(len, NIL);
                 }
 
-                self.locals[offset + (self.used_locals - *frame_locals)] = value;
-                self.used_locals += 1;
-                *frame_locals += 1;
-            }
-            OpCode::Drop(_) => {
-                *frame_locals -= 1;
-                self.used_locals -= 1;
+                self.locals[slot] = value;
+
+                // a slot can be saved more than once, like when a match arm fails and the next one
+                // binds its variables again, or on tail calls, so the frame only grows to fit it
+                if offset >= *frame_locals {
+                    self.used_locals += offset + 1 - *frame_locals;
+                    *frame_locals = offset + 1;
+                }
             }
 
             // globals manipulation
@@ -307,7 +482,7 @@ impl VirtualMachine {
                     Some(value) => value,
                     None => raise!(NameError, "Undefined variable '{}'", name)?,
                 };
-                self.push(value);
+                self.push(value)?;
             }
             OpCode::Savg(name) => {
                 let value = self.pop();
@@ -316,26 +491,29 @@ impl VirtualMachine {
                 }
                 self.set_global(name, value);
             }
+            OpCode::Redefg(name) => {
+                let value = self.pop();
+                self.set_global(name, value);
+            }
 
             // list manipulation
             OpCode::Prep => {
                 let list: List = self.pop().get()?;
                 let value = self.pop();
 
-                self.push(list.prepend(value).into());
-            }
-
-            OpCode::New => {
-                todo!()
+                self.push(list.prepend(value).into())?;
             }
 
             OpCode::Type => {
                 let value = self.pop();
-                self.push(Value::Module(value.type_of()));
+                self.push(Value::Module(value.type_of()))?;
             }
 
             OpCode::Ref(method) => {
-                let ty: GcRef<YexModule> = self.pop().get()?;
+                let ty: GcRef<YexModule> = match self.pop() {
+                    Value::Nil => raise!(TypeError, "Cannot access '{}' on nil", method)?,
+                    value => value.get()?,
+                };
 
                 let method = ty.fields.get(&method).ok_or(raise_err!(
                     FieldError,
@@ -344,7 +522,7 @@ impl VirtualMachine {
                     ty.name
                 ))?;
 
-                self.push(method);
+                self.push(method)?;
             }
 
             OpCode::Tup(len) => {
@@ -352,30 +530,75 @@ impl VirtualMachine {
                 for _ in 0..len {
                     tup.push(self.pop());
                 }
-                self.push(tup.into());
+                self.push(tup.into())?;
             }
 
             OpCode::TupGet(index) => {
                 let tup: Tuple = self.pop().get()?;
                 let elem = tup.0.get(index).unwrap(); // this SHOULD be unreachable
-                self.push(elem.clone());
+                self.push(elem.clone())?;
+            }
+
+            OpCode::Struct(len) => {
+                let mut fields: Vec<(Symbol, Value)> = vec![];
+                for _ in 0..len {
+                    let name = self.pop().get()?;
+                    let value = self.pop();
+
+                    match fields.iter_mut().find(|(field, _)| *field == name) {
+                        Some(field) => field.1 = value,
+                        None => fields.push((name, value)),
+                    }
+                }
+                self.push(Value::Struct(fields.into()))?;
+            }
+
+            OpCode::Get(field) => {
+                let st: Struct = match self.pop() {
+                    Value::Nil => raise!(TypeError, "Cannot access '{}' on nil", field)?,
+                    value => value.get()?,
+                };
+                match st.get(field) {
+                    Some(value) => self.push(value.clone())?,
+                    None => raise!(FieldError, "Struct has no field '{}'", field)?,
+                }
+            }
+
+            OpCode::Set(field) => {
+                let value = self.pop();
+                let st: Struct = match self.pop() {
+                    Value::Nil => raise!(TypeError, "Cannot access '{}' on nil", field)?,
+                    value => value.get()?,
+                };
+
+                let mut fields = (*st.0).clone();
+                match fields.iter_mut().find(|(name, _)| *name == field) {
+                    Some(slot) => slot.1 = value,
+                    None => raise!(NameError, "Struct has no field '{}'", field)?,
+                }
+                self.push(Value::Struct(fields.into()))?;
+            }
+
+            OpCode::Has(field) => {
+                let has = matches!(self.pop(), Value::Struct(st) if st.get(field).is_some());
+                self.push(has.into())?;
             }
 
             OpCode::Tag(tag) => {
                 let module: GcRef<YexModule> = self.pop().get()?;
                 let tup: Tuple = self.pop().get()?;
 
-                self.push(Value::Tagged(module, tag, tup));
+                self.push(Value::Tagged(module, tag, tup))?;
             }
 
             OpCode::TagOf => match self.pop() {
-                Value::Tagged(_, tag, _) => self.push(tag.into()),
-                _ => self.push(NIL),
+                Value::Tagged(_, tag, _) => self.push(tag.into())?,
+                _ => self.push(NIL)?,
             },
 
             OpCode::TagTup => {
                 let (_, _, tup) = self.pop().get()?;
-                self.push(tup.into());
+                self.push(tup.into())?;
             }
 
             // these opcodes are handled by the run function, since they can manipulate the ip
@@ -383,6 +606,7 @@ impl VirtualMachine {
             | OpCode::EndTry
             | OpCode::Jmp(..)
             | OpCode::Jmf(..)
+            | OpCode::Call(..)
             | OpCode::TCall(..) => unreachable!(),
         };
 
@@ -422,8 +646,33 @@ impl VirtualMachine {
         Some(args)
     }
 
+    /// Calls a function from native code, running it to completion
     #[inline]
     pub(crate) fn call(&mut self, arity: usize) -> InterpretResult<()> {
+        // every callback recurses on the native stack
+        if self.native_base.abs_diff(native_position()) > NATIVE_STACK_BUDGET {
+            return raise!(StackOverflow, "Stack overflow, callbacks nested too deeply");
+        }
+
+        let Some(body) = self.enter(arity)? else {
+            return Ok(());
+        };
+
+        let (line, column) = (self.line, self.column);
+        let res = match &*body {
+            FnKind::Bytecode(bytecode) => self.run(bytecode),
+            FnKind::Native(_) => unreachable!(),
+        };
+        (self.line, self.column) = (line, column);
+
+        self.frames -= 1;
+        self.used_locals -= 1;
+        res
+    }
+
+    /// Pops a function and its arguments. Native functions and partial applications are done
+    /// right away, for a bytecode function its frame is entered and its body returned to be run
+    fn enter(&mut self, arity: usize) -> InterpretResult<Option<GcRef<FnKind>>> {
         let fun: GcRef<Fn> = self.pop().get()?;
 
         if arity < fun.arity {
@@ -433,8 +682,8 @@ impl VirtualMachine {
                 args.push(self.pop());
             }
 
-            self.push(Value::Fn(GcRef::new(fun.apply(&args))));
-            return Ok(());
+            self.push(Value::Fn(GcRef::new(fun.apply(&args))))?;
+            return Ok(None);
         }
 
         let args = self.call_args(arity, &fun);
@@ -447,29 +696,50 @@ impl VirtualMachine {
             )?;
         }
 
-        match &*fun.body {
-            FnKind::Bytecode(bytecode) => self.call_bytecode(bytecode, args),
-            FnKind::Native(ptr) => self.call_native(*ptr, args),
+        if let FnKind::Native(ptr) = &*fun.body {
+            self.call_native(*ptr, args)?;
+            return Ok(None);
+        }
+
+        if self.frames >= MAX_FRAMES {
+            return raise!(
+                StackOverflow,
+                "Stack overflow, more than {} nested calls",
+                MAX_FRAMES
+            );
+        }
+
+        if self.frames >= self.max_depth {
+            return raise!(
+                RecursionError,
+                "Maximum recursion depth of {} exceeded",
+                self.max_depth
+            );
+        }
+
+        for arg in args.into_iter().flatten() {
+            self.push(arg)?;
         }
-    }
 
-    #[inline]
-    fn call_bytecode(
-        &mut self,
-        bytecode: BytecodeRef,
-        args: Option<FnArgs>,
-    ) -> InterpretResult<()> {
         self.used_locals += 1;
+        self.frames += 1;
 
-        args.map(|stack| {
-            for arg in stack {
-                self.push(arg);
-            }
-        });
+        Ok(Some(fun.body.clone()))
+    }
 
-        self.run(bytecode)?;
-        self.used_locals -= 1;
-        Ok(())
+    /// Releases the frame and makes its caller the current one, returning `false` if there's no
+    /// caller. The callee moved the position, so the caller's is restored to locate errors raised
+    /// after the call
+    fn return_to_caller(&mut self, frame: &mut Frame, callers: &mut Vec<Frame>) -> bool {
+        let Some(caller) = callers.pop() else {
+            return false;
+        };
+
+        self.used_locals -= frame.locals + 1;
+        self.frames -= 1;
+        (self.line, self.column) = (caller.line, caller.column);
+        *frame = caller;
+        true
     }
 
     #[inline]
@@ -497,9 +767,19 @@ impl VirtualMachine {
         }
     }
 
+    /// Pushes a value, raising a `StackOverflow` when the stack is full
     #[track_caller]
-    pub(crate) fn push(&mut self, constant: Value) {
+    pub(crate) fn push(&mut self, constant: Value) -> InterpretResult<()> {
+        if self.stack.len() >= STACK_SIZE {
+            return raise!(
+                StackOverflow,
+                "Stack overflow, more than {} values",
+                STACK_SIZE
+            );
+        }
+
         self.stack.push(constant);
+        Ok(())
     }
 
     #[track_caller]
@@ -514,7 +794,7 @@ impl VirtualMachine {
     {
         let a = self.pop();
         let b = self.pop();
-        Ok(self.push(f(b, a)?.into()))
+        self.push(f(b, a)?.into())
     }
 
     fn pop_two(&mut self) -> (Value, Value) {
@@ -524,7 +804,7 @@ impl VirtualMachine {
     }
 
     fn try_push(&mut self, constant: InterpretResult<Value>) -> InterpretResult<()> {
-        Ok(self.push(constant?))
+        self.push(constant?)
     }
 }
 
@@ -534,11 +814,348 @@ impl Default for VirtualMachine {
 
         let prelude = prelude::prelude();
         Self {
-            stack: STACK,
-            locals: vec![NIL; u16::MAX.into()],
+            stack: Box::new(STACK),
+            locals: vec![NIL; MAX_LOCALS],
             used_locals: 0,
             constants: Vec::new(),
             globals: prelude,
+            tasks: VecDeque::new(),
+            channels: Vec::new(),
+            line: 1,
+            column: 1,
+            frames: 0,
+            native_base: 0,
+            max_depth: MAX_FRAMES,
+            instructions: 0,
+            instruction_limit: None,
+            output: Box::new(io::stdout()),
+            input: None,
+            started: Instant::now(),
+            rng: Rng::from_time(),
+        }
+    }
+}
+
+#[test]
+fn over_duplicates_second_value() {
+    let mut vm = VirtualMachine::default();
+    vm.set_consts(vec![Value::Num(1.0), Value::Num(2.0)]);
+
+    let bytecode = [OpCode::Push(0), OpCode::Push(1), OpCode::Over]
+        .into_iter()
+        .map(|op| OpCodeMetadata::new(0, 0, op))
+        .collect();
+    vm.run(&bytecode).unwrap();
+
+    assert_eq!(vm.pop(), Value::Num(1.0));
+    assert_eq!(vm.pop(), Value::Num(2.0));
+    assert_eq!(vm.pop(), Value::Num(1.0));
+}
+
+#[test]
+fn every_opcode_is_dispatched() {
+    // adding an opcode without listing it here fails to compile
+    fn listed(op: OpCode) {
+        match op {
+            OpCode::Nop
+            | OpCode::Push(_)
+            | OpCode::Pop
+            | OpCode::Dup
+            | OpCode::Over
+            | OpCode::Load(_)
+            | OpCode::Save(_)
+            | OpCode::Loag(_)
+            | OpCode::Savg(_)
+            | OpCode::Redefg(_)
+            | OpCode::Jmf(_)
+            | OpCode::Jmp(_)
+            | OpCode::Call(_)
+            | OpCode::TCall(_)
+            | OpCode::Prep
+            | OpCode::Rev
+            | OpCode::RevN(_)
+            | OpCode::Add
+            | OpCode::Rem
+            | OpCode::Sub
+            | OpCode::Mul
+            | OpCode::Div
+            | OpCode::Neg
+            | OpCode::Len
+            | OpCode::Not
+            | OpCode::Xor
+            | OpCode::Shr
+            | OpCode::Shl
+            | OpCode::BitAnd
+            | OpCode::BitOr
+            | OpCode::Eq
+            | OpCode::Less
+            | OpCode::LessEq
+            | OpCode::Type
+            | OpCode::Ref(_)
+            | OpCode::Try(_)
+            | OpCode::EndTry
+            | OpCode::Tup(_)
+            | OpCode::TupGet(_)
+            | OpCode::Struct(_)
+            | OpCode::Get(_)
+            | OpCode::Set(_)
+            | OpCode::Has(_)
+            | OpCode::Tag(_)
+            | OpCode::TagOf
+            | OpCode::TagTup => (),
+        }
+    }
+
+    let name = Symbol::from("x");
+    let ops = [
+        OpCode::Nop,
+        OpCode::Push(0),
+        OpCode::Pop,
+        OpCode::Dup,
+        OpCode::Over,
+        OpCode::Load(0),
+        OpCode::Save(0),
+        OpCode::Loag(name),
+        OpCode::Savg(name),
+        OpCode::Redefg(name),
+        OpCode::Jmf(5),
+        OpCode::Jmp(5),
+        OpCode::Call(0),
+        OpCode::TCall(0),
+        OpCode::Prep,
+        OpCode::Rev,
+        OpCode::RevN(2),
+        OpCode::Add,
+        OpCode::Rem,
+        OpCode::Sub,
+        OpCode::Mul,
+        OpCode::Div,
+        OpCode::Neg,
+        OpCode::Len,
+        OpCode::Not,
+        OpCode::Xor,
+        OpCode::Shr,
+        OpCode::Shl,
+        OpCode::BitAnd,
+        OpCode::BitOr,
+        OpCode::Eq,
+        OpCode::Less,
+        OpCode::LessEq,
+        OpCode::Type,
+        OpCode::Ref(name),
+        OpCode::Try(5),
+        OpCode::EndTry,
+        OpCode::Tup(2),
+        OpCode::TupGet(0),
+        OpCode::Struct(1),
+        OpCode::Get(name),
+        OpCode::Set(name),
+        OpCode::Has(name),
+        OpCode::Tag(name),
+        OpCode::TagOf,
+        OpCode::TagTup,
+    ];
+
+    for op in ops {
+        listed(op);
+
+        let mut vm = VirtualMachine::default();
+        vm.set_consts(vec![Value::Num(1.0)]);
+
+        let bytecode = [OpCode::Push(0); 4]
+            .into_iter()
+            .chain([op])
+            .map(|op| OpCodeMetadata::new(0, 0, op))
+            .collect();
+
+        // raising is fine, the opcode only has to be handled without panicking
+        let _ = vm.run(&bytecode);
+    }
+}
+
+#[test]
+fn restoring_globals_drops_new_definitions() {
+    let mut vm = VirtualMachine::default();
+    vm.set_consts(vec![Value::Num(1.0)]);
+
+    let snapshot = vm.snapshot_globals();
+
+    let bytecode = [OpCode::Push(0), OpCode::Savg(Symbol::from("sandboxed"))]
+        .into_iter()
+        .map(|op| OpCodeMetadata::new(0, 0, op))
+        .collect();
+    vm.run(&bytecode).unwrap();
+    assert_eq!(vm.get_global("sandboxed"), Some(Value::Num(1.0)));
+
+    vm.restore_globals(snapshot);
+    assert_eq!(vm.get_global("sandboxed"), None);
+    assert!(vm.get_global("println").is_some());
+
+    // the name is free again
+    vm.run(&bytecode).unwrap();
+    assert_eq!(vm.get_global("sandboxed"), Some(Value::Num(1.0)));
+}
+
+#[test]
+fn infinite_loops_hit_the_instruction_limit() {
+    let mut vm = VirtualMachine::default();
+    vm.set_instruction_limit(1000);
+
+    // an infinite loop inside of a `try`, the limit can't be rescued
+    let bytecode = [OpCode::Try(3), OpCode::Nop, OpCode::Jmp(1), OpCode::Nop]
+        .into_iter()
+        .map(|op| OpCodeMetadata::new(4, 2, op))
+        .collect();
+
+    let err = vm.run(&bytecode).unwrap_err();
+    assert_eq!(err.err, Symbol::from("LimitExceeded"));
+    assert_eq!((err.line, err.column), (4, 2));
+
+    // the count starts over on every run
+    let bytecode = vec![OpCodeMetadata::new(1, 1, OpCode::Nop); 1000];
+    vm.run(&bytecode).unwrap();
+    vm.run(&bytecode).unwrap();
+}
+
+#[test]
+fn output_can_be_captured() {
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Clone, Default)]
+    struct Captured(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
         }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let captured = Captured::default();
+    let mut vm = VirtualMachine::default();
+    vm.set_output(captured.clone());
+    vm.set_consts(vec![Value::Num(1.0), "two".to_string().into()]);
+
+    let bytecode = [
+        OpCode::Push(0),
+        OpCode::Loag(Symbol::from("print")),
+        OpCode::Call(1),
+        OpCode::Push(1),
+        OpCode::Loag(Symbol::from("println")),
+        OpCode::Call(1),
+    ]
+    .into_iter()
+    .map(|op| OpCodeMetadata::new(0, 0, op))
+    .collect();
+    vm.run(&bytecode).unwrap();
+
+    assert_eq!(captured.0.borrow().as_slice(), b"1two\n");
+}
+
+#[test]
+fn input_can_be_fed() {
+    let mut vm = VirtualMachine::default();
+    vm.set_output(io::sink());
+    vm.set_input(io::Cursor::new("first line\r\nsecond"));
+    vm.set_consts(vec!["> ".to_string().into()]);
+
+    let read_line = [
+        OpCode::Push(0),
+        OpCode::Loag(Symbol::from("input")),
+        OpCode::Call(1),
+    ]
+    .into_iter()
+    .map(|op| OpCodeMetadata::new(0, 0, op))
+    .collect();
+
+    vm.run(&read_line).unwrap();
+    assert_eq!(vm.pop(), Value::from("first line".to_string()));
+
+    vm.run(&read_line).unwrap();
+    assert_eq!(vm.pop(), Value::from("second".to_string()));
+}
+
+#[test]
+fn errors_are_located_per_vm() {
+    fn error_line(line: usize) -> usize {
+        let mut vm = VirtualMachine::default();
+        vm.set_consts(vec![Value::Num(1.0), Value::Nil]);
+
+        let bytecode = vec![
+            OpCodeMetadata::new(1, 1, OpCode::Push(0)),
+            OpCodeMetadata::new(line, 1, OpCode::Push(1)),
+            OpCodeMetadata::new(line, 3, OpCode::Add),
+        ];
+
+        vm.run(&bytecode).unwrap_err().line
+    }
+
+    let threads: Vec<_> = [3, 7]
+        .into_iter()
+        .map(|line| {
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    assert_eq!(error_line(line), line);
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn saving_a_slot_again_does_not_grow_the_frame() {
+    fn used_locals(vm: *mut VirtualMachine, _: Vec<Value>) -> InterpretResult<Value> {
+        Ok(Value::Num(unsafe { &*vm }.used_locals as f64))
+    }
+
+    let mut vm = VirtualMachine::default();
+    let fun = Fn::new_native(1, used_locals);
+    vm.set_consts(vec![Value::Nil, Value::Fn(GcRef::new(fun))]);
+
+    let bytecode = [
+        OpCode::Push(0),
+        OpCode::Save(0),
+        OpCode::Push(0),
+        OpCode::Save(0),
+        OpCode::Push(0),
+        OpCode::Save(1),
+        OpCode::Push(0),
+        OpCode::Save(0),
+        OpCode::Push(0),
+        OpCode::Push(1),
+        OpCode::Call(1),
+    ]
+    .into_iter()
+    .map(|op| OpCodeMetadata::new(0, 0, op))
+    .collect();
+    vm.run(&bytecode).unwrap();
+
+    assert_eq!(vm.pop(), Value::Num(2.0));
+}
+
+#[test]
+fn accessing_nil_names_the_field() {
+    for op in [
+        OpCode::Ref(Symbol::from("len")),
+        OpCode::Get(Symbol::from("x")),
+    ] {
+        let mut vm = VirtualMachine::default();
+        vm.set_consts(vec![NIL]);
+
+        let bytecode = [OpCode::Push(0), op]
+            .into_iter()
+            .map(|op| OpCodeMetadata::new(0, 0, op))
+            .collect();
+        let err = vm.run(&bytecode).unwrap_err();
+
+        assert_eq!(err.err, Symbol::from("TypeError"));
+        assert!(err.msg.ends_with("on nil"), "{}", err.msg);
     }
 }
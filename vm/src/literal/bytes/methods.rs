@@ -0,0 +1,70 @@
+use std::fs;
+
+use crate::{
+    error::InterpretResult,
+    gc::GcRef,
+    literal::{nil, result, TryGet},
+    Value, VirtualMachine,
+};
+
+pub fn len(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let bytes: GcRef<Vec<u8>> = args[0].get()?;
+
+    Ok((bytes.len() as f64).into())
+}
+
+pub fn get(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let bytes: GcRef<Vec<u8>> = args[1].get()?;
+    let index: usize = args[0].get()?;
+
+    Ok(bytes
+        .get(index)
+        .map_or_else(nil, |byte| f64::from(*byte).into()))
+}
+
+pub fn to_str(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let bytes: GcRef<Vec<u8>> = args[0].get()?;
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned().into())
+}
+
+pub fn read(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+
+    match fs::read(path) {
+        Ok(bytes) => Ok(result::ok(vec![Value::Bytes(GcRef::new(bytes))])),
+        Err(err) => Ok(result::fail(vec![err.to_string().into()])),
+    }
+}
+
+pub fn write(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    let bytes: GcRef<Vec<u8>> = args[1].get()?;
+
+    match fs::write(path, &*bytes) {
+        Ok(()) => Ok(result::ok(vec![nil()])),
+        Err(err) => Ok(result::fail(vec![err.to_string().into()])),
+    }
+}
+
+#[test]
+fn binary_round_trip() {
+    let path = std::env::temp_dir().join("yex_bytes_round_trip.bin");
+    let path: Value = path.to_string_lossy().into_owned().into();
+    let blob: Vec<u8> = (0..=255).rev().collect();
+
+    let written = write(
+        std::ptr::null_mut(),
+        vec![path.clone(), Value::Bytes(GcRef::new(blob.clone()))],
+    )
+    .unwrap();
+    assert!(matches!(written, Value::Tagged(_, tag, _) if tag.as_str() == "Result.ok"));
+
+    let read = read(std::ptr::null_mut(), vec![path]).unwrap();
+    match read {
+        Value::Tagged(_, tag, tup) if tag.as_str() == "Result.ok" => {
+            assert_eq!(tup.0[0], Value::Bytes(GcRef::new(blob)));
+        }
+        other => panic!("expected Result.ok, found {other}"),
+    }
+}
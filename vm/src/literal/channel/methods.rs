@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+
+use crate::{
+    error::InterpretResult,
+    literal::nil,
+    prelude::task::{self, RECV},
+    raise, Value, VirtualMachine,
+};
+
+/// Gets the handle of a `Channel` value
+pub(crate) fn channel_of(value: &Value) -> InterpretResult<usize> {
+    match value {
+        Value::Channel(id) => Ok(*id),
+        other => raise!(
+            TypeError,
+            "Unexpected type '{}', expected type was 'Channel'",
+            other.type_name()
+        ),
+    }
+}
+
+pub fn new(vm: *mut VirtualMachine, _: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    vm.channels.push(VecDeque::new());
+    Ok(Value::Channel(vm.channels.len() - 1))
+}
+
+pub fn send(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+    let id = channel_of(&args[0])?;
+
+    vm.channels[id].push_back(args[1].clone());
+    Ok(nil())
+}
+
+/// Suspends the current task until the channel has a value, which is then passed to `k`
+pub fn recv(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    channel_of(&args[0])?;
+
+    Ok(task::suspend(RECV, vec![args[0].clone(), args[1].clone()]))
+}
@@ -1,9 +1,9 @@
-use std::fmt::Write;
+use std::{cmp::Ordering, fmt::Write};
 
 use crate::{
     error::InterpretResult,
-    literal::{nil, TryGet, Value},
-    VirtualMachine,
+    literal::{nil, position, TryGet, Value},
+    raise, Tuple, VirtualMachine,
 };
 
 use super::List;
@@ -21,8 +21,8 @@ pub fn map(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
     let xs: InterpretResult<List> = xs
         .iter()
         .map(|it| {
-            vm.push(it);
-            vm.push(fun.clone());
+            vm.push(it)?;
+            vm.push(fun.clone())?;
             vm.call(1)?;
             Ok(vm.pop())
         })
@@ -42,9 +42,29 @@ pub fn fold(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
     let fun = args[0].clone();
 
     for it in xs.iter() {
-        vm.push(it);
-        vm.push(acc);
-        vm.push(fun.clone());
+        vm.push(it)?;
+        vm.push(acc)?;
+        vm.push(fun.clone())?;
+
+        vm.call(2)?;
+
+        acc = vm.pop();
+    }
+
+    Ok(acc)
+}
+
+pub fn foldr(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let xs: List = args[2].get()?;
+    let mut acc = args[1].clone();
+    let fun = args[0].clone();
+
+    for it in xs.iter().collect::<Vec<_>>().into_iter().rev() {
+        vm.push(acc)?;
+        vm.push(it)?;
+        vm.push(fun.clone())?;
 
         vm.call(2)?;
 
@@ -63,8 +83,8 @@ pub fn filter(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Valu
     let mut ys = List::new();
 
     for x in xs.iter() {
-        vm.push(x.clone());
-        vm.push(fun.clone());
+        vm.push(x.clone())?;
+        vm.push(fun.clone())?;
 
         vm.call(1)?;
 
@@ -88,9 +108,9 @@ pub fn tail(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
 
 pub fn get(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let xs: List = args[1].get()?;
-    let n: usize = args[0].get()?;
+    let idx: isize = args[0].get()?;
 
-    Ok(xs.index(n))
+    Ok(xs.index(position(idx, xs.len())?))
 }
 
 pub fn drop(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
@@ -115,8 +135,8 @@ pub fn find(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
     let fun = &args[0];
 
     for x in xs.iter() {
-        vm.push(x.clone());
-        vm.push(fun.clone());
+        vm.push(x.clone())?;
+        vm.push(fun.clone())?;
 
         vm.call(1)?;
 
@@ -128,6 +148,87 @@ pub fn find(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
     Ok(nil())
 }
 
+pub fn any(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let xs: List = args[1].get()?;
+    let fun = &args[0];
+
+    for x in xs.iter() {
+        vm.push(x)?;
+        vm.push(fun.clone())?;
+
+        vm.call(1)?;
+
+        if vm.pop().to_bool() {
+            return Ok(true.into());
+        }
+    }
+
+    Ok(false.into())
+}
+
+pub fn all(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let xs: List = args[1].get()?;
+    let fun = &args[0];
+
+    for x in xs.iter() {
+        vm.push(x)?;
+        vm.push(fun.clone())?;
+
+        vm.call(1)?;
+
+        if !vm.pop().to_bool() {
+            return Ok(false.into());
+        }
+    }
+
+    Ok(true.into())
+}
+
+pub fn count_where(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let xs: List = args[1].get()?;
+    let fun = &args[0];
+
+    let mut count = 0;
+    for x in xs.iter() {
+        vm.push(x)?;
+        vm.push(fun.clone())?;
+
+        vm.call(1)?;
+
+        if vm.pop().to_bool() {
+            count += 1;
+        }
+    }
+
+    Ok(Value::Num(f64::from(count)))
+}
+
+pub fn contains(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[1].get()?;
+    let elem = &args[0];
+
+    Ok(xs.iter().any(|x| &x == elem).into())
+}
+
+pub fn unique(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    let mut seen: Vec<Value> = vec![];
+    for x in xs.iter() {
+        if !seen.contains(&x) {
+            seen.push(x);
+        }
+    }
+
+    Ok(seen.into_iter().rev().collect::<List>().into())
+}
+
 pub fn len(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let xs: List = args[0].get()?;
 
@@ -157,6 +258,285 @@ pub fn to_list(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Valu
     Ok(args[0].clone())
 }
 
+pub fn to_tuple(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    Ok(Tuple::from(xs.to_vec()).into())
+}
+
 pub fn new(_: *mut VirtualMachine, _: Vec<Value>) -> InterpretResult<Value> {
     Ok(List::new().into())
 }
+
+pub fn sum(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    xs.iter().try_fold(Value::Num(0.0), |acc, x| acc + x)
+}
+
+pub fn product(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    xs.iter().try_fold(Value::Num(1.0), |acc, x| acc * x)
+}
+
+fn numbers(xs: &List) -> InterpretResult<Vec<f64>> {
+    xs.iter().map(|x| x.get()).collect()
+}
+
+/// The running totals of a list, `[1, 2, 3]` gives `[1, 3, 6]`
+pub fn cumsum(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    let sums: Vec<_> = numbers(&xs)?
+        .into_iter()
+        .scan(0.0, |acc, x| {
+            *acc += x;
+            Some(Value::Num(*acc))
+        })
+        .collect();
+
+    Ok(sums.into_iter().rev().collect::<List>().into())
+}
+
+/// The differences between neighbours, `[1, 3, 6]` gives `[2, 3]`
+pub fn diffs(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    let diffs: Vec<_> = numbers(&xs)?
+        .windows(2)
+        .map(|pair| Value::Num(pair[1] - pair[0]))
+        .collect();
+
+    Ok(diffs.into_iter().rev().collect::<List>().into())
+}
+
+/// Moves `n` random elements to the front of `xs`, the first steps of a Fisher-Yates shuffle
+fn shuffle_front(vm: *mut VirtualMachine, xs: &mut [Value], n: usize) {
+    let rng = unsafe { &mut (*vm).rng };
+
+    for i in 0..n.min(xs.len().saturating_sub(1)) {
+        let j = i + rng.below(xs.len() - i);
+        xs.swap(i, j);
+    }
+}
+
+pub fn shuffle(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    let mut xs = xs.to_vec();
+    let len = xs.len();
+    shuffle_front(vm, &mut xs, len);
+    Ok(xs.into_iter().rev().collect::<List>().into())
+}
+
+/// `n` distinct elements of the list in a random order
+pub fn sample(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: usize = args[0].get()?;
+    let xs: List = args[1].get()?;
+
+    let mut xs = xs.to_vec();
+    if n > xs.len() {
+        raise!(
+            ValueError,
+            "Can't sample {} elements from a list with {}",
+            n,
+            xs.len()
+        )?;
+    }
+
+    shuffle_front(vm, &mut xs, n);
+    Ok(xs.into_iter().take(n).rev().collect::<List>().into())
+}
+
+pub fn concat(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+    let ys: List = args[1].get()?;
+
+    Ok(xs.concat(&ys).into())
+}
+
+/// Splices nested lists up to `depth` levels deep, using a stack of the lists being walked instead
+/// of recursion, so deeply nested lists can't overflow the native stack
+fn flatten_to(xs: List, depth: usize) -> List {
+    let mut flat = vec![];
+    let mut stack = vec![xs];
+
+    while let Some(xs) = stack.last_mut() {
+        let Some(x) = xs.head() else {
+            stack.pop();
+            continue;
+        };
+        *xs = xs.tail();
+
+        match x {
+            Value::List(inner) if stack.len() <= depth => stack.push(inner),
+            x => flat.push(x),
+        }
+    }
+
+    flat.into_iter().rev().collect()
+}
+
+pub fn flatten(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    Ok(flatten_to(xs, usize::MAX).into())
+}
+
+pub fn flatten_depth(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let depth: usize = args[0].get()?;
+    let xs: List = args[1].get()?;
+
+    Ok(flatten_to(xs, depth).into())
+}
+
+/// Pairs up the elements of two lists, stopping at the end of the shortest one
+pub fn zip(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+    let ys: List = args[1].get()?;
+
+    let pairs: Vec<Value> = xs
+        .iter()
+        .zip(ys.iter())
+        .map(|(x, y)| Tuple::from(vec![x, y]).into())
+        .collect();
+
+    Ok(pairs.into_iter().rev().collect::<List>().into())
+}
+
+/// Combines two lists element-wise with a function, stopping at the shorter one
+pub fn zip_with(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let fun = &args[0];
+    let xs: List = args[1].get()?;
+    let ys: List = args[2].get()?;
+
+    let mut zs = List::new();
+
+    for (x, y) in xs.iter().zip(ys.iter()) {
+        vm.push(y)?;
+        vm.push(x)?;
+        vm.push(fun.clone())?;
+
+        vm.call(2)?;
+
+        zs = zs.prepend(vm.pop());
+    }
+
+    Ok(zs.rev().into())
+}
+
+/// Splits a list of pairs into a tuple of two lists, the inverse of `zip`
+pub fn unzip(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let pairs: List = args[0].get()?;
+
+    let mut xs = vec![];
+    let mut ys = vec![];
+    for pair in pairs.iter() {
+        match pair {
+            Value::Tuple(tup) if tup.len() == 2 => {
+                xs.push(tup.0[0].clone());
+                ys.push(tup.0[1].clone());
+            }
+            other => raise!(
+                TypeError,
+                "Expected a tuple of two elements, found '{}'",
+                other
+            )?,
+        }
+    }
+
+    let xs: List = xs.into_iter().rev().collect();
+    let ys: List = ys.into_iter().rev().collect();
+    Ok(Tuple::from(vec![xs.into(), ys.into()]).into())
+}
+
+pub fn sort(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    let sorted = merge_sort(xs.to_vec(), &mut |a, b| a.ord_cmp(b))?;
+
+    Ok(sorted.into_iter().rev().collect::<List>().into())
+}
+
+pub fn sort_by(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let xs: List = args[1].get()?;
+    let fun = &args[0];
+
+    let sorted = merge_sort(xs.to_vec(), &mut |a, b| {
+        vm.push(b.clone())?;
+        vm.push(a.clone())?;
+        vm.push(fun.clone())?;
+
+        vm.call(2)?;
+
+        let ord: f64 = vm.pop().get()?;
+        Ok(ord.partial_cmp(&0.0).unwrap_or(Ordering::Equal))
+    })?;
+
+    Ok(sorted.into_iter().rev().collect::<List>().into())
+}
+
+/// Stable merge sort that stops at the first failing comparison
+fn merge_sort(
+    mut xs: Vec<Value>,
+    cmp: &mut impl FnMut(&Value, &Value) -> InterpretResult<Ordering>,
+) -> InterpretResult<Vec<Value>> {
+    if xs.len() <= 1 {
+        return Ok(xs);
+    }
+
+    let right = xs.split_off(xs.len() / 2);
+    let left = merge_sort(xs, cmp)?;
+    let right = merge_sort(right, cmp)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        if cmp(l, r)? == Ordering::Greater {
+            merged.push(right.next().unwrap());
+        } else {
+            merged.push(left.next().unwrap());
+        }
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+#[test]
+fn shuffle_is_deterministic_under_a_seed() {
+    let xs: List = (0..20).rev().map(|n| Value::Num(f64::from(n))).collect();
+    let mut vm = VirtualMachine::default();
+    let vm = std::ptr::addr_of_mut!(vm);
+
+    let shuffled = |seed| {
+        unsafe { (*vm).rng = crate::prelude::random::Rng::new(seed) };
+        let shuffled = shuffle(vm, vec![xs.clone().into()]).unwrap();
+        let sampled = sample(vm, vec![Value::Num(5.0), xs.clone().into()]).unwrap();
+        (shuffled, sampled)
+    };
+
+    let (a, sample_a) = shuffled(7);
+    let (b, sample_b) = shuffled(7);
+    assert_eq!(a, b);
+    assert_eq!(sample_a, sample_b);
+    assert_ne!(a, Value::List(xs.clone()));
+
+    let sorted = sort(vm, vec![a]).unwrap();
+    assert_eq!(sorted, Value::List(xs));
+
+    let sample: List = sample_a.get().unwrap();
+    assert_eq!(sample.len(), 5);
+    assert!(sample
+        .iter()
+        .all(|x| sample.iter().filter(|y| *y == x).count() == 1));
+}
@@ -6,12 +6,12 @@ use crate::{
 };
 
 type Link = Option<GcRef<Node>>;
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 /// Yex lists implementation
 pub struct List {
     head: Link,
 }
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct Node {
     elem: Value,
     next: Link,
@@ -27,7 +27,7 @@ impl List {
     /// Checks if the list is empty
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.head == None
+        self.head.is_none()
     }
 
     /// Prepends a value to the end, returning the list
@@ -78,7 +78,7 @@ impl List {
     pub fn len(&self) -> usize {
         let mut xs = self.head.as_ref();
         let mut count = 0;
-        while xs != None {
+        while xs.is_some() {
             xs = xs.unwrap().next.as_ref();
             count += 1;
         }
@@ -117,6 +117,14 @@ impl List {
         list
     }
 
+    /// Appends `other` to the end of `self`, sharing the nodes of `other`
+    #[must_use]
+    pub fn concat(&self, other: &Self) -> Self {
+        self.rev()
+            .iter()
+            .fold(other.clone(), |list, elem| list.prepend(elem))
+    }
+
     /// drop n items from Self
     #[must_use]
     pub fn drop(&self, mut len: usize) -> Self {
@@ -153,6 +161,33 @@ impl List {
     }
 }
 
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl Drop for List {
+    fn drop(&mut self) {
+        // unlinks the nodes owned only by this list one by one, including the ones of nested lists,
+        // so dropping a long or deeply nested list doesn't overflow the stack
+        let mut pending = vec![];
+        let mut next = self.head.take();
+
+        while let Some(mut node) = next.take().or_else(|| pending.pop()) {
+            if node.ref_count() > 1 {
+                continue;
+            }
+
+            let node = unsafe { node.mut_ref() };
+            next = node.next.take();
+            if let Value::List(xs) = &mut node.elem {
+                pending.extend(xs.head.take());
+            }
+        }
+    }
+}
+
 impl std::fmt::Display for List {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
@@ -0,0 +1,84 @@
+use crate::{error::InterpretResult, literal::TryGet, raise, List, Value, VirtualMachine};
+
+use super::Map;
+
+pub fn new(_: *mut VirtualMachine, _: Vec<Value>) -> InterpretResult<Value> {
+    Ok(Value::Map(Map::new()))
+}
+
+/// Builds a map from a list of `(key, value)` tuples, later keys overwrite earlier ones
+pub fn from_list(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let xs: List = args[0].get()?;
+
+    let mut map = Map::new();
+    for x in xs.iter() {
+        match x {
+            Value::Tuple(tup) if tup.len() == 2 => {
+                map = map.insert(tup.0[0].clone(), tup.0[1].clone())?;
+            }
+            other => raise!(
+                TypeError,
+                "Expected a tuple of two elements, found '{}'",
+                other
+            )?,
+        }
+    }
+
+    Ok(Value::Map(map))
+}
+
+pub fn to_list(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[0].get()?;
+
+    let pairs: List = map
+        .iter()
+        .rev()
+        .map(|(key, value)| vec![key.clone(), value.clone()].into())
+        .collect();
+    Ok(pairs.into())
+}
+
+/// The value of a key, or `nil` if it isn't in the map
+pub fn get(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[1].get()?;
+
+    Ok(map.get(&args[0])?.cloned().unwrap_or(Value::Nil))
+}
+
+pub fn has(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[1].get()?;
+
+    Ok(map.get(&args[0])?.is_some().into())
+}
+
+pub fn insert(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[2].get()?;
+
+    Ok(Value::Map(map.insert(args[0].clone(), args[1].clone())?))
+}
+
+pub fn remove(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[1].get()?;
+
+    Ok(Value::Map(map.remove(&args[0])?))
+}
+
+pub fn keys(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[0].get()?;
+
+    let keys: List = map.iter().rev().map(|(key, _)| key.clone()).collect();
+    Ok(keys.into())
+}
+
+pub fn values(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[0].get()?;
+
+    let values: List = map.iter().rev().map(|(_, value)| value.clone()).collect();
+    Ok(values.into())
+}
+
+pub fn len(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let map: Map = args[0].get()?;
+
+    Ok(Value::Num(map.len() as f64))
+}
@@ -0,0 +1,160 @@
+pub(crate) mod methods;
+
+use std::collections::HashMap;
+
+use crate::{error::InterpretResult, gc::GcRef, raise, Symbol, Value};
+
+/// The hashable form of a map key, numbers are hashed by their bits
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Num(u64),
+    Str(String),
+    Sym(Symbol),
+    Bool(bool),
+    Nil,
+    Tuple(Vec<Key>),
+}
+
+impl Key {
+    fn new(value: &Value) -> InterpretResult<Self> {
+        let key = match value {
+            Value::Num(n) if n.is_nan() => raise!(ValueError, "NaN can't be used as a map key")?,
+            // `-0 == 0`, so they must be the same key
+            Value::Num(n) if *n == 0.0 => Key::Num(0f64.to_bits()),
+            Value::Num(n) => Key::Num(n.to_bits()),
+            Value::Str(s) => Key::Str(s.to_string()),
+            Value::Sym(s) => Key::Sym(s.0),
+            Value::Bool(b) => Key::Bool(*b),
+            Value::Nil => Key::Nil,
+            Value::Tuple(xs) => Key::Tuple(xs.0.iter().map(Key::new).collect::<Result<_, _>>()?),
+            other => raise!(
+                TypeError,
+                "Values of type '{}' can't be used as map keys",
+                other.type_name()
+            )?,
+        };
+
+        Ok(key)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Table {
+    index: HashMap<Key, usize>,
+    /// The key and value pairs in insertion order, which keeps `keys` and printing deterministic
+    entries: Vec<(Value, Value)>,
+}
+
+#[derive(Debug, Clone)]
+/// A yex map, a hash table from numbers, strings, symbols, booleans, nil or tuples of them to any
+/// value. Maps are immutable, `insert` and `remove` return a new map
+pub struct Map(GcRef<Table>);
+
+impl Map {
+    /// Creates an empty map
+    #[must_use]
+    pub fn new() -> Self {
+        Self(GcRef::new(Table::default()))
+    }
+
+    /// Returns the value of a key, raising a `TypeError` if the key isn't hashable
+    pub fn get(&self, key: &Value) -> InterpretResult<Option<&Value>> {
+        let key = Key::new(key)?;
+        Ok(self.0.index.get(&key).map(|&i| &self.0.entries[i].1))
+    }
+
+    /// Returns a copy of the map with the key set to the value
+    pub fn insert(&self, key: Value, value: Value) -> InterpretResult<Self> {
+        let hashed = Key::new(&key)?;
+        let mut table = (*self.0).clone();
+
+        if let Some(&i) = table.index.get(&hashed) {
+            table.entries[i].1 = value;
+        } else {
+            table.index.insert(hashed, table.entries.len());
+            table.entries.push((key, value));
+        }
+
+        Ok(Self(GcRef::new(table)))
+    }
+
+    /// Returns a copy of the map without the key
+    pub fn remove(&self, key: &Value) -> InterpretResult<Self> {
+        let hashed = Key::new(key)?;
+        let mut table = (*self.0).clone();
+
+        if let Some(removed) = table.index.remove(&hashed) {
+            table.entries.remove(removed);
+            for i in table.index.values_mut() {
+                if *i > removed {
+                    *i -= 1;
+                }
+            }
+        }
+
+        Ok(Self(GcRef::new(table)))
+    }
+
+    /// Iterates over the key and value pairs in insertion order
+    #[must_use]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(Value, Value)> {
+        self.0.entries.iter()
+    }
+
+    /// Returns the number of entries
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.entries.len()
+    }
+
+    /// checks if the map has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.entries.is_empty()
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Map{{{}}}",
+            self.iter()
+                .map(|(key, value)| format!("{key}: {value}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+#[test]
+fn remove_keeps_the_other_entries_reachable() {
+    let key = |s: &str| Value::from(s.to_string());
+
+    let mut map = Map::new();
+    for (i, name) in ["a", "b", "c", "d"].into_iter().enumerate() {
+        map = map.insert(key(name), Value::Num(i as f64)).unwrap();
+    }
+    let removed = map.remove(&key("b")).unwrap();
+
+    assert_eq!(removed.len(), 3);
+    assert_eq!(removed.get(&key("b")).unwrap(), None);
+    assert_eq!(removed.get(&key("d")).unwrap(), Some(&Value::Num(3.0)));
+    // the original map is untouched
+    assert_eq!(map.get(&key("b")).unwrap(), Some(&Value::Num(1.0)));
+
+    assert_eq!(
+        map.insert(Value::Num(-0.0), Value::Nil)
+            .unwrap()
+            .get(&Value::Num(0.0))
+            .unwrap(),
+        Some(&Value::Nil)
+    );
+    assert!(map.get(&Value::List(crate::List::new())).is_err());
+}
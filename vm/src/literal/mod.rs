@@ -1,21 +1,26 @@
 use std::{
     any::Any,
+    cell::Cell,
     cmp::Ordering,
-    mem,
     ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub},
 };
 
 //pub mod file;
+pub mod bytes;
+pub mod channel;
 pub mod ffi;
 pub mod fun;
 pub mod list;
+pub mod map;
+pub mod num;
 pub mod result;
 pub mod str;
+pub mod r#struct;
 pub mod symbol;
 pub mod tuple;
 pub mod yexmodule;
 
-use crate::{error::InterpretResult, gc::GcRef, raise, VirtualMachine};
+use crate::{env::EnvTable, error::InterpretResult, gc::GcRef, raise, VirtualMachine};
 
 use fun::Fn;
 use list::List;
@@ -24,6 +29,8 @@ use yexmodule::YexModule;
 
 use self::{
     ffi::{userdata::UserData, Ffi},
+    map::Map,
+    r#struct::Struct,
     symbol::YexSymbol,
     tuple::Tuple,
 };
@@ -32,16 +39,20 @@ pub fn show(_: *mut VirtualMachine, x: Vec<Value>) -> InterpretResult<String> {
     match &x[0] {
         Value::Sym(s) => Ok(s.to_string()),
         Value::Str(s) => Ok(s.to_string()),
+        bytes @ Value::Bytes(_) => Ok(bytes.to_string()),
         Value::List(l) => Ok(l.to_string()),
         Value::Tuple(t) => Ok(t.to_string()),
+        Value::Struct(s) => Ok(s.to_string()),
+        Value::Map(m) => Ok(m.to_string()),
         tag @ Value::Tagged(..) => Ok(tag.to_string()),
-        Value::Num(n) => Ok(n.to_string()),
+        Value::Num(n) => Ok(num::show(*n)),
         Value::Bool(b) => Ok(b.to_string()),
         Value::FFI(f) => Ok(f.to_string()),
+        channel @ Value::Channel(_) => Ok(channel.to_string()),
         Value::UserData(u) => Ok(format!("<userdata({:?})>", u.type_id())),
         Value::Fn(f) => Ok(format!("fn({})", f.arity)),
         Value::Nil => Ok("nil".to_string()),
-        Value::Module(m) => Ok(format!("type '{}'", m.name.as_str())),
+        Value::Module(m) => Ok(m.to_string()),
     }
 }
 
@@ -50,6 +61,26 @@ pub fn nil() -> Value {
     Value::Nil
 }
 
+/// Turns an index into a position in a collection of `len` elements, negative indices count from
+/// the end, so `-1` is the last element
+pub(crate) fn position(idx: isize, len: usize) -> InterpretResult<usize> {
+    let pos = if idx < 0 {
+        len.checked_sub(idx.unsigned_abs())
+    } else {
+        Some(idx.unsigned_abs())
+    };
+
+    match pos {
+        Some(pos) if pos < len => Ok(pos),
+        _ => raise!(
+            IndexError,
+            "Index {} is out of range for a collection with {} elements",
+            idx,
+            len
+        ),
+    }
+}
+
 impl From<Vec<Value>> for Value {
     fn from(vec: Vec<Value>) -> Self {
         Value::Tuple(Tuple::from(vec))
@@ -105,12 +136,14 @@ impl From<Fn> for Value {
 }
 
 /// Immediate values that can be consumed
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub enum Value {
     /// float-precision numbers
     Num(f64),
     /// Strings
     Str(GcRef<String>),
+    /// Raw binary data
+    Bytes(GcRef<Vec<u8>>),
     /// erlang-like atoms
     Sym(YexSymbol),
     /// Booleans
@@ -123,37 +156,135 @@ pub enum Value {
     Module(GcRef<YexModule>),
     /// Tuples
     Tuple(Tuple),
+    /// Structs, a set of named fields
+    Struct(Struct),
+    /// Hash maps from hashable values to values
+    Map(Map),
     /// Tagged tuples
     Tagged(GcRef<YexModule>, Symbol, Tuple),
     /// FFI User Data
     UserData(UserData),
     /// External Libraries
     FFI(Ffi),
+    /// Handle to a channel owned by the virtual machine
+    Channel(usize),
     /// null
     Nil,
 }
 
 impl Clone for Value {
     fn clone(&self) -> Self {
-        use Value::{Bool, Fn, List, Module, Nil, Num, Str, Sym, Tagged, Tuple, UserData, FFI};
+        use Value::{
+            Bool, Bytes, Channel, Fn, List, Map, Module, Nil, Num, Str, Struct, Sym, Tagged, Tuple,
+            UserData, FFI,
+        };
 
         match self {
             List(xs) => List(xs.clone()),
             Str(str) => Str(GcRef::clone(str)),
+            Bytes(bytes) => Bytes(GcRef::clone(bytes)),
             Fn(f) => Fn(GcRef::clone(f)),
             Bool(b) => Bool(*b),
             Num(n) => Num(*n),
             Sym(s) => Sym(*s),
             Module(t) => Module(t.clone()),
             Tuple(t) => Tuple(t.clone()),
+            Struct(s) => Struct(s.clone()),
+            Map(m) => Map(m.clone()),
             FFI(f) => FFI(f.clone()),
             UserData(u) => UserData(u.clone()),
             Tagged(m, s, t) => Tagged(m.clone(), *s, t.clone()),
+            Channel(id) => Channel(*id),
             Nil => Nil,
         }
     }
 }
 
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        use Value::{
+            Bool, Bytes, Channel, Fn, List, Map, Module, Nil, Num, Str, Struct, Sym, Tagged, Tuple,
+            UserData, FFI,
+        };
+
+        // nested values are compared with an explicit stack instead of recursion, so deeply nested
+        // lists can't overflow the stack
+        let mut pending = vec![(self.clone(), other.clone())];
+
+        while let Some(pair) = pending.pop() {
+            let equal = match pair {
+                (List(l), List(r)) => {
+                    if l.len() != r.len() {
+                        return false;
+                    }
+                    pending.extend(l.iter().zip(r.iter()));
+                    true
+                }
+                (Tuple(l), Tuple(r)) => {
+                    if l.len() != r.len() {
+                        return false;
+                    }
+                    pending.extend(l.0.iter().cloned().zip(r.0.iter().cloned()));
+                    true
+                }
+                // fields are compared by name, so their order doesn't matter
+                (Struct(l), Struct(r)) => {
+                    if l.len() != r.len() {
+                        return false;
+                    }
+                    for (name, value) in l.0.iter() {
+                        match r.get(*name) {
+                            Some(other) => pending.push((value.clone(), other.clone())),
+                            None => return false,
+                        }
+                    }
+                    true
+                }
+                // so are the entries of maps
+                (Map(l), Map(r)) => {
+                    if l.len() != r.len() {
+                        return false;
+                    }
+                    for (key, value) in l.iter() {
+                        match r.get(key) {
+                            Ok(Some(other)) => pending.push((value.clone(), other.clone())),
+                            _ => return false,
+                        }
+                    }
+                    true
+                }
+                // the module of a tagged value holds its nullary variants, so it's compared by name
+                // to avoid looping through them
+                (Tagged(lm, lt, l), Tagged(rm, rt, r)) => {
+                    if lm.name != rm.name || lt != rt || l.len() != r.len() {
+                        return false;
+                    }
+                    pending.extend(l.0.iter().cloned().zip(r.0.iter().cloned()));
+                    true
+                }
+                (Num(l), Num(r)) => l == r,
+                (Str(l), Str(r)) => l == r,
+                (Bytes(l), Bytes(r)) => l == r,
+                (Sym(l), Sym(r)) => l == r,
+                (Bool(l), Bool(r)) => l == r,
+                (Fn(l), Fn(r)) => l == r,
+                (Module(l), Module(r)) => l == r,
+                (UserData(l), UserData(r)) => l == r,
+                (FFI(l), FFI(r)) => l == r,
+                (Channel(l), Channel(r)) => l == r,
+                (Nil, Nil) => true,
+                _ => false,
+            };
+
+            if !equal {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 impl Value {
     /// checks if the constant is `nil`
     #[must_use]
@@ -161,58 +292,104 @@ impl Value {
         self == &Self::Nil
     }
 
-    /// Returns the size of `self`
-    #[must_use]
+    /// Returns the number of elements of a collection, strings are measured in bytes
     #[allow(clippy::len_without_is_empty)]
-    pub fn len(&self) -> usize {
+    pub fn len(&self) -> InterpretResult<usize> {
         match self {
-            Value::List(xs) => xs.len(),
-            Value::Num(_) => mem::size_of::<f64>(),
-            Value::Sym(_) => mem::size_of::<Symbol>(),
-            Value::Str(s) => s.len(),
-            Value::Fn(f) => mem::size_of_val(&f),
-            Value::Bool(_) => mem::size_of::<bool>(),
-            Value::Module(t) => mem::size_of_val(&t),
-            Value::Tuple(t) | Value::Tagged(_, _, t) => t.len(),
-            Value::FFI(f) => mem::size_of_val(f),
-            Value::UserData(d) => mem::size_of_val(d),
-            Value::Nil => 4,
+            Value::List(xs) => Ok(xs.len()),
+            Value::Str(s) => Ok(s.len()),
+            Value::Bytes(b) => Ok(b.len()),
+            Value::Tuple(t) | Value::Tagged(_, _, t) => Ok(t.len()),
+            Value::Struct(s) => Ok(s.len()),
+            Value::Map(m) => Ok(m.len()),
+            other => raise!(
+                TypeError,
+                "Values of type '{}' have no length",
+                other.type_name()
+            ),
         }
     }
 
     /// Compares the left and the right value
+    ///
+    /// Strings are compared lexicographically, booleans with `false < true`, symbols by their
+    /// name and lists/tuples element by element, using the length as a tiebreaker
     pub fn ord_cmp(&self, rhs: &Self) -> InterpretResult<Ordering> {
-        let (left, right) = match (self, rhs) {
-            (Self::Num(left), Self::Num(right)) => (left, right),
-            (l, r) => raise!(TypeError, "cmp not supported with '{}' and '{}'", l, r)?,
-        };
+        self.ord_cmp_at(rhs, 0)
+    }
 
-        match left.partial_cmp(right) {
-            Some(ord) => Ok(ord),
-            None => raise!(TypeError, "Cannot compare '{}' and '{}'", left, right),
+    fn ord_cmp_at(&self, rhs: &Self, depth: usize) -> InterpretResult<Ordering> {
+        if depth > MAX_CMP_DEPTH {
+            return raise!(
+                RecursionError,
+                "Values nested more than {} levels deep can't be compared",
+                MAX_CMP_DEPTH
+            );
+        }
+
+        match (self, rhs) {
+            (Self::Num(left), Self::Num(right)) => match left.partial_cmp(right) {
+                Some(ord) => Ok(ord),
+                None => raise!(TypeError, "Cannot compare '{}' and '{}'", left, right),
+            },
+            (Self::Str(left), Self::Str(right)) => Ok(left.as_str().cmp(right.as_str())),
+            (Self::Bool(left), Self::Bool(right)) => Ok(left.cmp(right)),
+            (Self::Sym(left), Self::Sym(right)) => Ok(left.as_str().cmp(right.as_str())),
+            (Self::List(left), Self::List(right)) => {
+                Self::seq_cmp(left.iter(), right.iter(), depth)
+            }
+            (Self::Tuple(left), Self::Tuple(right)) => {
+                Self::seq_cmp(left.0.iter().cloned(), right.0.iter().cloned(), depth)
+            }
+            (l, r) => raise!(TypeError, "cmp not supported with '{}' and '{}'", l, r),
+        }
+    }
+
+    fn seq_cmp(
+        mut left: impl Iterator<Item = Value>,
+        mut right: impl Iterator<Item = Value>,
+        depth: usize,
+    ) -> InterpretResult<Ordering> {
+        loop {
+            match (left.next(), right.next()) {
+                (Some(l), Some(r)) => match l.ord_cmp_at(&r, depth + 1)? {
+                    Ordering::Equal => {}
+                    ord => return Ok(ord),
+                },
+                (Some(_), None) => return Ok(Ordering::Greater),
+                (None, Some(_)) => return Ok(Ordering::Less),
+                (None, None) => return Ok(Ordering::Equal),
+            }
         }
     }
 
     /// Convert the constant to a boolean
     #[must_use]
     pub fn to_bool(&self) -> bool {
-        use Value::{Bool, Fn, List, Module, Nil, Num, Str, Sym, Tagged, Tuple, UserData, FFI};
+        use Value::{
+            Bool, Bytes, Channel, Fn, List, Map, Module, Nil, Num, Str, Struct, Sym, Tagged, Tuple,
+            UserData, FFI,
+        };
 
         match self {
             Bool(b) => *b,
             Str(s) if s.is_empty() => false,
+            Bytes(b) => !b.is_empty(),
             Num(n) if *n == 0.0 => false,
             Nil => false,
             List(xs) => !xs.is_empty(),
-            Sym(_) | Str(_) | Num(_) | Fn(_) | FFI(_) | Module(_) | Tuple(_) | Tagged(..)
-            | UserData(_) => true,
+            Sym(_) | Str(_) | Num(_) | Fn(_) | FFI(_) | Module(_) | Tuple(_) | Struct(_)
+            | Map(_) | Tagged(..) | UserData(_) | Channel(_) => true,
         }
     }
 
     /// returns the type of the value
     #[must_use]
     pub fn type_of(&self) -> GcRef<YexModule> {
-        use Value::{Bool, Fn, List, Module, Nil, Num, Str, Sym, Tagged, Tuple, UserData, FFI};
+        use Value::{
+            Bool, Bytes, Channel, Fn, List, Map, Module, Nil, Num, Str, Struct, Sym, Tagged, Tuple,
+            UserData, FFI,
+        };
 
         match self {
             Module(t) | Tagged(t, _, _) => return t.clone(),
@@ -224,16 +401,49 @@ impl Value {
             Fn(_) => YexModule::fun(),
             Num(_) => YexModule::num(),
             Str(_) => YexModule::str(),
+            Bytes(_) => YexModule::bytes(),
             Bool(_) => YexModule::bool(),
             Nil => YexModule::nil(),
             Sym(_) => YexModule::sym(),
             Tuple(_) => YexModule::tuple(),
+            Struct(_) => YexModule::r#struct(),
+            Map(_) => YexModule::map(),
             FFI(_) => YexModule::ffi(),
-            Module(_) | UserData(_) | Tagged(..) => unreachable!(),
+            Channel(_) => YexModule::channel(),
+            UserData(_) => YexModule::new(Symbol::from("UserData"), EnvTable::new()),
+            Module(_) | Tagged(..) => unreachable!(),
         };
 
         GcRef::new(ty)
     }
+
+    /// Returns the name of the value's type, the same as `type_of().name` without building the
+    /// whole module and its method table
+    #[must_use]
+    pub fn type_name(&self) -> &str {
+        use Value::{
+            Bool, Bytes, Channel, Fn, List, Map, Module, Nil, Num, Str, Struct, Sym, Tagged, Tuple,
+            UserData, FFI,
+        };
+
+        match self {
+            Module(t) | Tagged(t, _, _) => t.name.as_str(),
+            List(_) => "List",
+            Fn(_) => "Fn",
+            Num(_) => "Num",
+            Str(_) => "Str",
+            Bytes(_) => "Bytes",
+            Bool(_) => "Bool",
+            Nil => "Nil",
+            Sym(_) => "Sym",
+            Tuple(_) => "Tuple",
+            Struct(_) => "Struct",
+            Map(_) => "Map",
+            FFI(_) => "FFI",
+            Channel(_) => "Channel",
+            UserData(_) => "UserData",
+        }
+    }
 }
 
 impl Default for Value {
@@ -250,20 +460,52 @@ impl From<Value> for bool {
     }
 }
 
+/// How deep `ord_cmp` goes into nested lists and tuples before giving up
+const MAX_CMP_DEPTH: usize = 1024;
+
+/// How deep `Display` goes into nested values before printing `...`, this keeps self-referential
+/// values from recursing forever
+const MAX_DISPLAY_DEPTH: usize = 64;
+
+thread_local! {
+    static DISPLAY_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use Value::{Bool, Fn, List, Module, Nil, Num, Str, Sym, Tagged, Tuple, UserData, FFI};
+        let depth = DISPLAY_DEPTH.with(|it| it.replace(it.get() + 1));
+        let result = if depth >= MAX_DISPLAY_DEPTH {
+            write!(f, "...")
+        } else {
+            self.display(f)
+        };
+        DISPLAY_DEPTH.with(|it| it.set(depth));
+
+        result
+    }
+}
+
+impl Value {
+    fn display(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Value::{
+            Bool, Bytes, Channel, Fn, List, Map, Module, Nil, Num, Str, Struct, Sym, Tagged, Tuple,
+            UserData, FFI,
+        };
         let tk = match self {
             Fn(f) => format!("fn({})", f.arity),
             Nil => "nil".to_string(),
             List(xs) => format!("{}", *xs),
-            Str(s) => "\"".to_owned() + s + "\"",
+            Str(s) => str::quote(s),
+            Bytes(b) => format!("b\"{}\"", b.escape_ascii()),
             Sym(s) => format!("{}", s),
-            Num(n) => n.to_string(),
-            Module(t) => format!("type '{}'", t.name),
+            Num(n) => num::show(*n),
+            Module(t) => t.to_string(),
             Tuple(t) => format!("{t}"),
+            Struct(s) => format!("{s}"),
+            Map(m) => format!("{m}"),
             UserData(u) => format!("<userdata({:?})>", u.type_id()),
             FFI(f) => f.to_string(),
+            Channel(id) => format!("<channel({id})>"),
             Tagged(_, tag, value) => {
                 write!(f, "({}", tag.as_str())?;
                 for item in value.0.iter() {
@@ -279,13 +521,26 @@ impl std::fmt::Display for Value {
     }
 }
 
+/// Raises a `ZeroDivisionError` when dividing a number by zero
+fn nonzero_divisor(op: &str, lhs: &Value, rhs: &Value) -> InterpretResult<()> {
+    match (lhs, rhs) {
+        (Value::Num(_), Value::Num(y)) if *y == 0.0 => raise!(
+            ZeroDivisionError,
+            "Cannot apply '{}' operator with a zero divisor",
+            op
+        ),
+        _ => Ok(()),
+    }
+}
+
 macro_rules! impl_numeric {
-    ($($t:ident $op:tt $fn:ident);+$(;)?) => {
+    ($($t:ident $op:tt $fn:ident $(if $check:ident)?);+$(;)?) => {
         $(
             impl $t for Value {
                 type Output = ConstantErr;
 
                 fn $fn(self, rhs: Self) -> Self::Output {
+                    $($check(stringify!($op), &self, &rhs)?;)?
                     match (self, rhs) {
                         (Self::Num(x), Self::Num(y)) => Ok(Self::Num(x $op y)),
                         (Self::Str(x), Self::Str(y)) => Ok(Self::Str(GcRef::new(x.to_string() + &y))),
@@ -301,8 +556,8 @@ impl_numeric!(
     Add + add;
     Sub - sub;
     Mul * mul;
-    Div / div;
-    Rem % rem;
+    Div / div if nonzero_divisor;
+    Rem % rem if nonzero_divisor;
 );
 
 macro_rules! impl_bit {
@@ -361,7 +616,7 @@ macro_rules! impl_get {
             fn get(&self) -> InterpretResult<$to> {
                 match self {
                     Self::$pattern(x) => Ok(x.clone()),
-                    _ => crate::raise!(TypeError, "Unexpected type '{}', expected type was '{}'", self.type_of().name, stringify!($pattern)),
+                    _ => crate::raise!(TypeError, "Unexpected type '{}', expected type was '{}'", self.type_name(), stringify!($pattern)),
                 }
             }
         }
@@ -373,7 +628,7 @@ macro_rules! impl_get {
                 use Value::*;
                 match self {
                     $pattern($($tt)+) => Ok($parse_expr),
-                    _ => crate::raise!(TypeError, "Unexpected type '{}', expected type was '{}'", self.type_of().name, stringify!($pattern)),
+                    _ => crate::raise!(TypeError, "Unexpected type '{}', expected type was '{}'", self.type_name(), stringify!($pattern)),
                 }
             }
         }
@@ -381,6 +636,7 @@ macro_rules! impl_get {
 }
 
 impl_get!(String: Str (s) => s.to_string());
+impl_get!(GcRef<Vec<u8>>: Bytes);
 impl_get!(f64: Num);
 impl_get!(bool: Bool);
 impl_get!(GcRef<YexModule>: Module);
@@ -390,6 +646,8 @@ impl_get!(List: List);
 impl_get!(Ffi: FFI);
 impl_get!(UserData: UserData);
 impl_get!(Tuple: Tuple);
+impl_get!(Struct: Struct);
+impl_get!(Map: Map);
 impl_get!((GcRef<YexModule>, Symbol, Tuple): Tagged(m, s, t) => (m.clone(), *s, t.clone()));
 impl_get!(usize: Num(n) => {
     if n.fract() != 0.0 || n.is_nan() || n.is_infinite() || *n < 0.0 {
@@ -406,3 +664,88 @@ impl_get!(isize: Num(n) => {
 
     n.round() as isize
 });
+
+#[test]
+fn ord_cmp_strings() {
+    let abc = Value::from("abc".to_string());
+    let abd = Value::from("abd".to_string());
+    let ab = Value::from("ab".to_string());
+
+    assert_eq!(abc.ord_cmp(&abd).unwrap(), Ordering::Less);
+    assert_eq!(abd.ord_cmp(&abc).unwrap(), Ordering::Greater);
+    assert_eq!(ab.ord_cmp(&abc).unwrap(), Ordering::Less);
+    assert_eq!(abc.ord_cmp(&abc).unwrap(), Ordering::Equal);
+}
+
+#[test]
+fn ord_cmp_nested_lists() {
+    let list = |xs: Vec<Value>| Value::List(xs.into_iter().rev().collect());
+
+    let a = list(vec![list(vec![1.0.into(), 2.0.into()]), 3.0.into()]);
+    let b = list(vec![list(vec![1.0.into(), 3.0.into()]), 0.0.into()]);
+    let c = list(vec![list(vec![1.0.into(), 2.0.into()])]);
+
+    assert_eq!(a.ord_cmp(&b).unwrap(), Ordering::Less);
+    assert_eq!(c.ord_cmp(&a).unwrap(), Ordering::Less);
+    assert_eq!(a.ord_cmp(&a).unwrap(), Ordering::Equal);
+}
+
+#[test]
+fn ord_cmp_mixed_types() {
+    let num = Value::Num(1.0);
+    let str = Value::from("1".to_string());
+
+    let err = num.ord_cmp(&str).unwrap_err();
+    assert_eq!(err.err, Symbol::new("TypeError"));
+}
+
+#[test]
+fn display_self_referential_value() {
+    let mut tup = Tuple::from(vec![Value::Nil]);
+    unsafe { tup.0.mut_ref()[0] = Value::Tuple(tup.clone()) };
+
+    let shown = Value::Tuple(tup.clone()).to_string();
+    assert!(shown.contains("(...)"));
+
+    // breaks the cycle so the tuple can be freed
+    unsafe { tup.0.mut_ref()[0] = Value::Nil };
+}
+
+#[test]
+fn compare_deep_lists() {
+    let nested = |depth: usize| {
+        (0..depth).fold(Value::List(List::new()), |xs, _| {
+            Value::List(List::new().prepend(xs))
+        })
+    };
+    let long: Value = Value::List((0..1_000_000).map(|n| Value::Num(n.into())).collect());
+
+    assert_eq!(nested(100_000), nested(100_000));
+    assert_ne!(nested(100_000), nested(99_999));
+    assert_eq!(long, long.clone());
+
+    let err = nested(100_000).ord_cmp(&nested(100_000)).unwrap_err();
+    assert_eq!(err.err, Symbol::new("RecursionError"));
+}
+
+#[test]
+fn type_name_matches_type_of() {
+    let module = GcRef::new(YexModule::new(Symbol::from("Shape"), EnvTable::new()));
+    let values = [
+        Value::Num(1.0),
+        Value::from("a".to_string()),
+        Value::Bool(true),
+        Value::Nil,
+        Value::Sym(Symbol::from("a").into()),
+        Value::List(List::new()),
+        Value::Tuple(Tuple::from(vec![Value::Nil])),
+        Value::Struct(Struct::from(vec![(Symbol::from("x"), Value::Nil)])),
+        Value::Fn(GcRef::new(Fn::new_native(1, list::methods::rev))),
+        Value::Module(module.clone()),
+        Value::Tagged(module, Symbol::from("Shape.circle"), Tuple::from(vec![])),
+    ];
+
+    for value in values {
+        assert_eq!(value.type_name(), value.type_of().name.as_str());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{error::InterpretResult, literal::TryGet, raise, Value, VirtualMachine};
+
+pub fn sqrt(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+
+    if n < 0.0 {
+        raise!(
+            ValueError,
+            "Can't take the square root of a negative number ('{}')",
+            n
+        )?;
+    }
+
+    Ok(n.sqrt().into())
+}
+
+pub fn pow(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let base: f64 = args[0].get()?;
+    let exp: f64 = args[1].get()?;
+
+    Ok(base.powf(exp).into())
+}
+
+pub fn abs(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.abs().into())
+}
+
+pub fn floor(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.floor().into())
+}
+
+pub fn ceil(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.ceil().into())
+}
+
+pub fn round(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.round().into())
+}
+
+pub fn sin(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.sin().into())
+}
+
+pub fn cos(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.cos().into())
+}
+
+pub fn tan(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.tan().into())
+}
+
+pub fn exp(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    Ok(n.exp().into())
+}
+
+pub fn ln(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+
+    if n <= 0.0 {
+        raise!(ValueError, "Can't take the logarithm of '{}'", n)?;
+    }
+
+    Ok(n.ln().into())
+}
+
+pub fn log(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let n: f64 = args[0].get()?;
+    let base: f64 = args[1].get()?;
+
+    if n <= 0.0 {
+        raise!(ValueError, "Can't take the logarithm of '{}'", n)?;
+    }
+
+    if base <= 0.0 || (base - 1.0).abs() < f64::EPSILON {
+        raise!(ValueError, "Invalid logarithm base '{}'", base)?;
+    }
+
+    Ok(n.log(base).into())
+}
+
+fn gcd_of(a: isize, b: isize) -> usize {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn gcd(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let a: isize = args[0].get()?;
+    let b: isize = args[1].get()?;
+
+    Ok((gcd_of(a, b) as f64).into())
+}
+
+pub fn lcm(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let a: isize = args[0].get()?;
+    let b: isize = args[1].get()?;
+
+    if a == 0 || b == 0 {
+        return Ok(0.0.into());
+    }
+
+    match (a.unsigned_abs() / gcd_of(a, b)).checked_mul(b.unsigned_abs()) {
+        Some(n) => Ok((n as f64).into()),
+        None => raise!(ValueError, "lcm of '{}' and '{}' is too large", a, b),
+    }
+}
+
+/// `2^53 - 1`, past it `f64` can't represent every integer, so results would silently round
+const MAX_SAFE_INT: i64 = (1 << 53) - 1;
+
+fn safe_int(value: &Value) -> InterpretResult<i64> {
+    let n: isize = value.get()?;
+    let n = n as i64;
+
+    // `abs` would overflow on the saturated `i64::MIN`
+    if n.unsigned_abs() > MAX_SAFE_INT as u64 {
+        raise!(ValueError, "'{}' is too large to be an exact integer", n)?;
+    }
+    Ok(n)
+}
+
+/// Applies an integer operation, raising a `ValueError` instead of losing precision
+fn exact(args: &[Value], op: &str, f: fn(i64, i64) -> Option<i64>) -> InterpretResult<Value> {
+    let a = safe_int(&args[0])?;
+    let b = safe_int(&args[1])?;
+
+    match f(a, b) {
+        Some(n) if n.unsigned_abs() <= MAX_SAFE_INT as u64 => Ok((n as f64).into()),
+        _ => raise!(
+            ValueError,
+            "'{} {} {}' is too large to be an exact integer",
+            a,
+            op,
+            b
+        ),
+    }
+}
+
+pub fn exact_add(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    exact(&args, "+", i64::checked_add)
+}
+
+pub fn exact_sub(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    exact(&args, "-", i64::checked_sub)
+}
+
+pub fn exact_mul(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    exact(&args, "*", i64::checked_mul)
+}
+
+fn to_radix(args: &[Value], fmt: fn(usize) -> String) -> InterpretResult<Value> {
+    let n: isize = args[0].get()?;
+    let digits = fmt(n.unsigned_abs());
+
+    if n < 0 {
+        Ok(format!("-{digits}").into())
+    } else {
+        Ok(digits.into())
+    }
+}
+
+pub fn to_binary(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    to_radix(&args, |n| format!("{n:b}"))
+}
+
+pub fn to_octal(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    to_radix(&args, |n| format!("{n:o}"))
+}
+
+pub fn to_hex(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    to_radix(&args, |n| format!("{n:x}"))
+}
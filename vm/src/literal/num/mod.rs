@@ -0,0 +1,10 @@
+pub mod methods;
+
+/// Renders a number, `NaN` is written as `nan` to match `inf` and `-inf`
+pub(crate) fn show(n: f64) -> String {
+    if n.is_nan() {
+        "nan".to_string()
+    } else {
+        n.to_string()
+    }
+}
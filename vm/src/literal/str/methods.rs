@@ -1,19 +1,18 @@
 use crate::{
     error::InterpretResult,
     gc::GcRef,
-    literal::{nil, TryGet},
+    literal::{nil, position, show, TryGet},
+    prelude::json,
     raise, List, Value, VirtualMachine,
 };
 
 pub fn get(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let string: String = args[1].get()?;
-    let index: usize = args[0].get()?;
+    let idx: isize = args[0].get()?;
 
-    let char = string
-        .chars()
-        .nth(index)
-        .map_or_else(nil, |c| c.to_string().into());
-    Ok(char)
+    let pos = position(idx, string.chars().count())?;
+    let char = string.chars().nth(pos).unwrap_or_default();
+    Ok(char.to_string().into())
 }
 
 pub fn split(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
@@ -28,12 +27,38 @@ pub fn split(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
     Ok(list.rev().into())
 }
 
+pub fn split_whitespace(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let string: String = args[0].get()?;
+
+    let list: List = string
+        .split_whitespace()
+        .map(|str| str.to_owned().into())
+        .collect();
+
+    Ok(list.rev().into())
+}
+
+pub fn tokenize(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let string: String = args[1].get()?;
+    let delimiters: String = args[0].get()?;
+
+    let list: List = string
+        .split(|c| delimiters.contains(c))
+        .filter(|str| !str.is_empty())
+        .map(|str| str.to_owned().into())
+        .collect();
+
+    Ok(list.rev().into())
+}
+
 pub fn len(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let str: String = args[0].get()?;
 
     Ok((str.len() as f64).into())
 }
 
+/// Splits the string into Unicode scalar values, so a letter followed by a combining accent
+/// becomes two elements (see [`graphemes`] for user-perceived characters)
 pub fn chars(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let str: String = args[0].get()?;
     let iter = str.chars().map(|c| c.to_string().into());
@@ -41,6 +66,64 @@ pub fn chars(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
     Ok(iter.rev().collect::<List>().into())
 }
 
+/// Splits the string into grapheme clusters
+///
+/// This covers the common cases without the full Unicode segmentation tables: combining marks,
+/// variation selectors, emoji modifiers and zero width joiner sequences stay attached to the
+/// previous character, regional indicators are paired into flags and `\r\n` is kept together
+pub fn graphemes(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    let mut clusters: Vec<String> = vec![];
+    let mut prev: Option<char> = None;
+    let mut regional_run = 0;
+
+    for c in str.chars() {
+        let joins = match prev {
+            Some('\r') => c == '\n',
+            Some('\u{200D}') => true,
+            Some(p) if is_regional_indicator(p) && is_regional_indicator(c) => {
+                regional_run % 2 == 1
+            }
+            Some(_) => is_extend(c),
+            None => false,
+        };
+
+        regional_run = if is_regional_indicator(c) {
+            regional_run + 1
+        } else {
+            0
+        };
+
+        match clusters.last_mut() {
+            Some(cluster) if joins => cluster.push(c),
+            _ => clusters.push(c.to_string()),
+        }
+
+        prev = Some(c);
+    }
+
+    let list: List = clusters.into_iter().rev().map(Into::into).collect();
+    Ok(list.into())
+}
+
+fn is_extend(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{200D}'
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
 pub fn ord(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let str: String = args[0].get()?;
 
@@ -62,6 +145,190 @@ pub fn chr(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
 
     Ok(code.into())
 }
+pub fn upper(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    Ok(str.to_uppercase().into())
+}
+
+pub fn lower(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    Ok(str.to_lowercase().into())
+}
+
+pub fn trim(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    Ok(str.trim().to_string().into())
+}
+
+pub fn trim_start(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    Ok(str.trim_start().to_string().into())
+}
+
+pub fn trim_end(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    Ok(str.trim_end().to_string().into())
+}
+
+/// Returns the chars between `start` (inclusive) and `end` (exclusive)
+///
+/// Negative indices count from the end of the string and out of range indices are clamped
+pub fn slice(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+    let start: isize = args[1].get()?;
+    let end: isize = args[2].get()?;
+
+    let len = isize::try_from(str.chars().count()).unwrap_or(isize::MAX);
+    let normalize = |idx: isize| {
+        let idx = if idx < 0 { len + idx } else { idx };
+        idx.clamp(0, len) as usize
+    };
+
+    let (start, end) = (normalize(start), normalize(end));
+    if start >= end {
+        return Ok(String::new().into());
+    }
+
+    Ok(str
+        .chars()
+        .skip(start)
+        .take(end - start)
+        .collect::<String>()
+        .into())
+}
+
+/// Replaces each `{}` in the format string by the next value of the list, rendered with `show`
+///
+/// `{{` and `}}` are escapes for literal braces
+pub fn format(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let fmt: String = args[0].get()?;
+    let values: List = args[1].get()?;
+
+    let mut values = values.iter();
+    let mut out = String::new();
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                out.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                match values.next() {
+                    Some(value) => out.push_str(&show(vm, vec![value])?),
+                    None => raise!(ValueError, "Not enough arguments for format string")?,
+                }
+            }
+            ('{' | '}', _) => raise!(ValueError, "Unmatched '{}' in format string", c)?,
+            _ => out.push(c),
+        }
+    }
+
+    if values.next().is_some() {
+        raise!(ValueError, "Too many arguments for format string")?;
+    }
+
+    Ok(out.into())
+}
+
+pub fn contains(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[1].get()?;
+    let pattern: String = args[0].get()?;
+
+    Ok(str.contains(&pattern).into())
+}
+
+/// Returns the index of the first occurrence of the pattern, counted in chars (not bytes)
+pub fn index_of(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[1].get()?;
+    let pattern: String = args[0].get()?;
+
+    Ok(str.find(&pattern).map_or_else(nil, |byte_idx| {
+        (str[..byte_idx].chars().count() as f64).into()
+    }))
+}
+
+pub fn replace(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[2].get()?;
+    let from: String = args[0].get()?;
+    let to: String = args[1].get()?;
+
+    Ok(str.replace(&from, &to).into())
+}
+
+pub fn replace_all(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[2].get()?;
+    let from: String = args[0].get()?;
+
+    replace_with(vm, &str, &from, &args[1])
+}
+
+fn replace_with(
+    vm: *mut VirtualMachine,
+    str: &str,
+    from: &str,
+    fun: &Value,
+) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+
+    let mut result = String::with_capacity(str.len());
+    let mut last = 0;
+    for (idx, found) in str.match_indices(from) {
+        vm.push(found.to_string().into())?;
+        vm.push(fun.clone())?;
+        vm.call(1)?;
+        let to: String = vm.pop().get()?;
+
+        result.push_str(&str[last..idx]);
+        result.push_str(&to);
+        last = idx + found.len();
+    }
+    result.push_str(&str[last..]);
+
+    Ok(result.into())
+}
+
+pub fn from_json(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    json::from_json(&args)
+}
+
+pub fn to_bytes(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let str: String = args[0].get()?;
+
+    Ok(Value::Bytes(GcRef::new(str.into_bytes())))
+}
+
 pub fn new(_: *mut VirtualMachine, _: Vec<Value>) -> InterpretResult<Value> {
     Ok(Value::Str(GcRef::new(String::from(""))))
 }
+
+#[test]
+fn graphemes_keep_combining_marks() {
+    let str: Value = "e\u{301}a\r\n\u{1F1E7}\u{1F1F7}\u{1F468}\u{200D}\u{1F469}"
+        .to_string()
+        .into();
+
+    let scalars: List = chars(std::ptr::null_mut(), vec![str.clone()])
+        .unwrap()
+        .get()
+        .unwrap();
+    assert_eq!(scalars.len(), 10);
+
+    let clusters: List = graphemes(std::ptr::null_mut(), vec![str])
+        .unwrap()
+        .get()
+        .unwrap();
+    let expected: Vec<Value> = [
+        "e\u{301}",
+        "a",
+        "\r\n",
+        "\u{1F1E7}\u{1F1F7}",
+        "\u{1F468}\u{200D}\u{1F469}",
+    ]
+    .iter()
+    .map(|s| s.to_string().into())
+    .collect();
+    assert_eq!(clusters.to_vec(), expected);
+}
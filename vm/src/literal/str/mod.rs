@@ -1 +1,28 @@
 pub mod methods;
+
+use std::fmt::Write;
+
+/// Writes a string as a yex literal, with the escapes the lexer understands
+pub(crate) fn quote(str: &str) -> String {
+    let mut out = String::from('"');
+    let mut chars = str.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            // `${` would start an interpolation
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            c if c.is_ascii_control() => {
+                let _ = write!(out, "\\x{:02x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
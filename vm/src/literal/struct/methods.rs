@@ -0,0 +1,20 @@
+use crate::{error::InterpretResult, literal::TryGet, raise, Symbol, Value, VirtualMachine};
+
+use super::Struct;
+
+pub fn get(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let field: Symbol = args[0].get()?;
+    let st: Struct = args[1].get()?;
+
+    match st.get(field) {
+        Some(value) => Ok(value.clone()),
+        None => raise!(FieldError, "Struct has no field '{}'", field),
+    }
+}
+
+pub fn has(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let field: Symbol = args[0].get()?;
+    let st: Struct = args[1].get()?;
+
+    Ok(st.get(field).is_some().into())
+}
@@ -0,0 +1,50 @@
+pub(crate) mod methods;
+
+use crate::{gc::GcRef, Symbol, Value};
+
+#[derive(Debug, Clone)]
+/// A yex struct, the fields are kept in the order they were declared
+pub struct Struct(pub GcRef<Vec<(Symbol, Value)>>);
+
+impl From<Vec<(Symbol, Value)>> for Struct {
+    fn from(fields: Vec<(Symbol, Value)>) -> Self {
+        Struct(GcRef::new(fields))
+    }
+}
+
+impl Struct {
+    /// Returns the value of a field
+    #[must_use]
+    pub fn get(&self, field: Symbol) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the number of fields
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// checks if the struct has no fields
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Display for Struct {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "%{{{}}}",
+            self.0
+                .iter()
+                .map(|(name, value)| format!("{}: {value}", name.as_str()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
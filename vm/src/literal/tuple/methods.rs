@@ -2,17 +2,55 @@ use std::fmt::Write;
 
 use crate::{
     error::InterpretResult,
-    literal::{nil, TryGet},
-    Value, VirtualMachine,
+    literal::{position, TryGet},
+    raise, List, Value, VirtualMachine,
 };
 
 use super::Tuple;
 
 pub fn get(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
     let tup: Tuple = args[1].get()?;
-    let idx: usize = args[0].get()?;
+    let idx: isize = args[0].get()?;
+
+    Ok(tup.0[position(idx, tup.len())?].clone())
+}
+
+/// Raises an `IndexError` if the tuple is too short to have an element at `idx`
+fn check_index(tup: &Tuple, idx: usize) -> InterpretResult<()> {
+    if idx < tup.len() {
+        Ok(())
+    } else {
+        raise!(
+            IndexError,
+            "Index {} is out of range for a tuple with {} elements",
+            idx,
+            tup.len()
+        )
+    }
+}
+
+fn nth(tup: &Value, idx: usize) -> InterpretResult<Value> {
+    let tup: Tuple = tup.get()?;
+    check_index(&tup, idx)?;
+
+    Ok(tup.0[idx].clone())
+}
+
+pub fn first(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    nth(&args[0], 0)
+}
 
-    Ok(tup.0.get(idx).cloned().unwrap_or_else(nil))
+pub fn second(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    nth(&args[0], 1)
+}
+
+pub fn last(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let tup: Tuple = args[0].get()?;
+
+    match tup.0.last() {
+        Some(x) => Ok(x.clone()),
+        None => raise!(IndexError, "Can't get the last element of an empty tuple"),
+    }
 }
 
 pub fn show(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
@@ -37,3 +75,66 @@ pub fn show(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value>
 pub fn new(_: *mut VirtualMachine, _: Vec<Value>) -> InterpretResult<Value> {
     Ok(vec![].into())
 }
+
+pub fn map(vm: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let vm = unsafe { &mut *vm };
+    let tup: Tuple = args[1].get()?;
+    let fun = &args[0];
+
+    let mut xs = Vec::with_capacity(tup.len());
+    for x in tup.0.iter() {
+        vm.push(x.clone())?;
+        vm.push(fun.clone())?;
+        vm.call(1)?;
+        xs.push(vm.pop());
+    }
+
+    Ok(xs.into())
+}
+
+pub fn to_list(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let tup: Tuple = args[0].get()?;
+
+    Ok(tup.0.iter().rev().cloned().collect::<List>().into())
+}
+
+pub fn append(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let tup: Tuple = args[1].get()?;
+
+    let mut xs = Vec::with_capacity(tup.len() + 1);
+    xs.extend(tup.0.iter().cloned());
+    xs.push(args[0].clone());
+
+    Ok(xs.into())
+}
+
+pub fn concat(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let a: Tuple = args[0].get()?;
+    let b: Tuple = args[1].get()?;
+
+    Ok([&a.0[..], &b.0[..]].concat().into())
+}
+
+pub fn update(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let idx: usize = args[0].get()?;
+    let tup: Tuple = args[2].get()?;
+    check_index(&tup, idx)?;
+
+    let mut xs = tup.0.to_vec();
+    xs[idx] = args[1].clone();
+
+    Ok(xs.into())
+}
+
+pub fn swap(_: *mut VirtualMachine, args: Vec<Value>) -> InterpretResult<Value> {
+    let i: usize = args[0].get()?;
+    let j: usize = args[1].get()?;
+    let tup: Tuple = args[2].get()?;
+    check_index(&tup, i)?;
+    check_index(&tup, j)?;
+
+    let mut xs = tup.0.to_vec();
+    xs.swap(i, j);
+
+    Ok(xs.into())
+}
@@ -1,8 +1,10 @@
+use std::cell::Cell;
+
 use crate::{env::EnvTable, gc::GcRef, literal, Symbol, Value};
 
-use super::{fun::Fn, list, str, tuple};
+use super::{bytes, channel, fun::Fn, list, map, num, r#struct, str, tuple};
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Default)]
 /// A Yex user-defined type.
 pub struct YexModule {
     /// Module name.
@@ -10,6 +12,20 @@ pub struct YexModule {
     /// Module functions.
     pub fields: EnvTable,
 }
+
+/// Types are nominal, so two modules are the same type if they have the same name, even if they
+/// were built separately
+impl PartialEq for YexModule {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl std::fmt::Display for YexModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "type '{}'", self.name)
+    }
+}
 #[macro_export]
 /// Add Fields/Methods to a `YexModule`
 macro_rules! fields {
@@ -43,6 +59,32 @@ macro_rules! fields {
          )*
     };}
 
+thread_local! {
+    static IN_MODULE: Cell<bool> = const { Cell::new(false) };
+}
+
+impl std::fmt::Debug for YexModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the fields of a type hold its nullary variants, which point back at the type, so only the
+        // outermost module lists its fields
+        if IN_MODULE.with(|it| it.replace(true)) {
+            return f
+                .debug_struct("YexModule")
+                .field("name", &self.name)
+                .finish_non_exhaustive();
+        }
+
+        let result = f
+            .debug_struct("YexModule")
+            .field("name", &self.name)
+            .field("fields", &self.fields)
+            .finish();
+        IN_MODULE.with(|it| it.set(false));
+
+        result
+    }
+}
+
 impl YexModule {
     /// Creates a new Yex type.
     #[must_use]
@@ -80,6 +122,11 @@ impl YexModule {
             Value::Fn(GcRef::new(Fn::new_native(3, list::methods::fold))),
         );
 
+        methods.insert(
+            Symbol::from("foldr"),
+            Value::Fn(GcRef::new(Fn::new_native(3, list::methods::foldr))),
+        );
+
         methods.insert(
             Symbol::from("rev"),
             Value::Fn(GcRef::new(Fn::new_native(1, list::methods::rev))),
@@ -105,6 +152,31 @@ impl YexModule {
             Value::Fn(GcRef::new(Fn::new_native(2, list::methods::find))),
         );
 
+        methods.insert(
+            Symbol::from("any"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::any))),
+        );
+
+        methods.insert(
+            Symbol::from("all"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::all))),
+        );
+
+        methods.insert(
+            Symbol::from("countWhere"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::count_where))),
+        );
+
+        methods.insert(
+            Symbol::from("contains"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::contains))),
+        );
+
+        methods.insert(
+            Symbol::from("unique"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::unique))),
+        );
+
         methods.insert(
             Symbol::from("len"),
             Value::Fn(GcRef::new(Fn::new_native(1, list::methods::len))),
@@ -125,6 +197,81 @@ impl YexModule {
             Value::Fn(GcRef::new(Fn::new_native(1, list::methods::to_list))),
         );
 
+        methods.insert(
+            Symbol::from("toTuple"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::to_tuple))),
+        );
+
+        methods.insert(
+            Symbol::from("sort"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::sort))),
+        );
+
+        methods.insert(
+            Symbol::from("sort_by"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::sort_by))),
+        );
+
+        methods.insert(
+            Symbol::from("concat"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::concat))),
+        );
+
+        methods.insert(
+            Symbol::from("flatten"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::flatten))),
+        );
+
+        methods.insert(
+            Symbol::from("flattenDepth"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::flatten_depth))),
+        );
+
+        methods.insert(
+            Symbol::from("zip"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::zip))),
+        );
+
+        methods.insert(
+            Symbol::from("zipWith"),
+            Value::Fn(GcRef::new(Fn::new_native(3, list::methods::zip_with))),
+        );
+
+        methods.insert(
+            Symbol::from("unzip"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::unzip))),
+        );
+
+        methods.insert(
+            Symbol::from("sum"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::sum))),
+        );
+
+        methods.insert(
+            Symbol::from("product"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::product))),
+        );
+
+        methods.insert(
+            Symbol::from("shuffle"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::shuffle))),
+        );
+
+        methods.insert(
+            Symbol::from("sample"),
+            Value::Fn(GcRef::new(Fn::new_native(2, list::methods::sample))),
+        );
+
+        methods.insert(
+            Symbol::from("cumsum"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::cumsum))),
+        );
+
+        methods.insert(
+            Symbol::from("diffs"),
+            Value::Fn(GcRef::new(Fn::new_native(1, list::methods::diffs))),
+        );
+
         Self::new(Symbol::from("List"), methods)
     }
 
@@ -148,6 +295,56 @@ impl YexModule {
             Value::Fn(GcRef::new(Fn::new_native(1, tuple::methods::show))),
         );
 
+        methods.insert(
+            Symbol::from("map"),
+            Value::Fn(GcRef::new(Fn::new_native(2, tuple::methods::map))),
+        );
+
+        methods.insert(
+            Symbol::from("to_list"),
+            Value::Fn(GcRef::new(Fn::new_native(1, tuple::methods::to_list))),
+        );
+
+        methods.insert(
+            Symbol::from("toList"),
+            Value::Fn(GcRef::new(Fn::new_native(1, tuple::methods::to_list))),
+        );
+
+        methods.insert(
+            Symbol::from("append"),
+            Value::Fn(GcRef::new(Fn::new_native(2, tuple::methods::append))),
+        );
+
+        methods.insert(
+            Symbol::from("concat"),
+            Value::Fn(GcRef::new(Fn::new_native(2, tuple::methods::concat))),
+        );
+
+        methods.insert(
+            Symbol::from("first"),
+            Value::Fn(GcRef::new(Fn::new_native(1, tuple::methods::first))),
+        );
+
+        methods.insert(
+            Symbol::from("second"),
+            Value::Fn(GcRef::new(Fn::new_native(1, tuple::methods::second))),
+        );
+
+        methods.insert(
+            Symbol::from("last"),
+            Value::Fn(GcRef::new(Fn::new_native(1, tuple::methods::last))),
+        );
+
+        methods.insert(
+            Symbol::from("update"),
+            Value::Fn(GcRef::new(Fn::new_native(3, tuple::methods::update))),
+        );
+
+        methods.insert(
+            Symbol::from("swap"),
+            Value::Fn(GcRef::new(Fn::new_native(3, tuple::methods::swap))),
+        );
+
         Self::new(Symbol::from("Tuple"), methods)
     }
 
@@ -163,6 +360,106 @@ impl YexModule {
             }))),
         );
 
+        methods.insert(
+            Symbol::from("sqrt"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::sqrt))),
+        );
+
+        methods.insert(
+            Symbol::from("pow"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::pow))),
+        );
+
+        methods.insert(
+            Symbol::from("abs"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::abs))),
+        );
+
+        methods.insert(
+            Symbol::from("floor"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::floor))),
+        );
+
+        methods.insert(
+            Symbol::from("ceil"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::ceil))),
+        );
+
+        methods.insert(
+            Symbol::from("round"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::round))),
+        );
+
+        methods.insert(
+            Symbol::from("sin"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::sin))),
+        );
+
+        methods.insert(
+            Symbol::from("cos"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::cos))),
+        );
+
+        methods.insert(
+            Symbol::from("tan"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::tan))),
+        );
+
+        methods.insert(
+            Symbol::from("ln"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::ln))),
+        );
+
+        methods.insert(
+            Symbol::from("log"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::log))),
+        );
+
+        methods.insert(
+            Symbol::from("exp"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::exp))),
+        );
+
+        methods.insert(
+            Symbol::from("gcd"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::gcd))),
+        );
+
+        methods.insert(
+            Symbol::from("lcm"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::lcm))),
+        );
+
+        methods.insert(
+            Symbol::from("toBinary"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::to_binary))),
+        );
+
+        methods.insert(
+            Symbol::from("toOctal"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::to_octal))),
+        );
+
+        methods.insert(
+            Symbol::from("toHex"),
+            Value::Fn(GcRef::new(Fn::new_native(1, num::methods::to_hex))),
+        );
+
+        methods.insert(
+            Symbol::from("exactAdd"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::exact_add))),
+        );
+
+        methods.insert(
+            Symbol::from("exactSub"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::exact_sub))),
+        );
+
+        methods.insert(
+            Symbol::from("exactMul"),
+            Value::Fn(GcRef::new(Fn::new_native(2, num::methods::exact_mul))),
+        );
+
         Self::new(Symbol::from("Num"), methods)
     }
 
@@ -196,11 +493,29 @@ impl YexModule {
             Value::Fn(GcRef::new(Fn::new_native(2, str::methods::split))),
         );
 
+        methods.insert(
+            Symbol::new("splitWhitespace"),
+            Value::Fn(GcRef::new(Fn::new_native(
+                1,
+                str::methods::split_whitespace,
+            ))),
+        );
+
+        methods.insert(
+            Symbol::new("tokenize"),
+            Value::Fn(GcRef::new(Fn::new_native(2, str::methods::tokenize))),
+        );
+
         methods.insert(
             Symbol::new("toList"),
             Value::Fn(GcRef::new(Fn::new_native(1, str::methods::chars))),
         );
 
+        methods.insert(
+            Symbol::new("graphemes"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::graphemes))),
+        );
+
         methods.insert(
             Symbol::new("len"),
             Value::Fn(GcRef::new(Fn::new_native(1, str::methods::len))),
@@ -221,6 +536,71 @@ impl YexModule {
             Value::Fn(GcRef::new(Fn::new_native(1, str::methods::chr))),
         );
 
+        methods.insert(
+            Symbol::new("toBytes"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::to_bytes))),
+        );
+
+        methods.insert(
+            Symbol::new("upper"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::upper))),
+        );
+
+        methods.insert(
+            Symbol::new("lower"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::lower))),
+        );
+
+        methods.insert(
+            Symbol::new("trim"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::trim))),
+        );
+
+        methods.insert(
+            Symbol::new("trim_start"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::trim_start))),
+        );
+
+        methods.insert(
+            Symbol::new("trim_end"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::trim_end))),
+        );
+
+        methods.insert(
+            Symbol::new("contains"),
+            Value::Fn(GcRef::new(Fn::new_native(2, str::methods::contains))),
+        );
+
+        methods.insert(
+            Symbol::new("index_of"),
+            Value::Fn(GcRef::new(Fn::new_native(2, str::methods::index_of))),
+        );
+
+        methods.insert(
+            Symbol::new("replace"),
+            Value::Fn(GcRef::new(Fn::new_native(3, str::methods::replace))),
+        );
+
+        methods.insert(
+            Symbol::new("fromJson"),
+            Value::Fn(GcRef::new(Fn::new_native(1, str::methods::from_json))),
+        );
+
+        methods.insert(
+            Symbol::new("replaceAll"),
+            Value::Fn(GcRef::new(Fn::new_native(3, str::methods::replace_all))),
+        );
+
+        methods.insert(
+            Symbol::new("slice"),
+            Value::Fn(GcRef::new(Fn::new_native(3, str::methods::slice))),
+        );
+
+        methods.insert(
+            Symbol::new("format"),
+            Value::Fn(GcRef::new(Fn::new_native(2, str::methods::format))),
+        );
+
         methods.insert(
             Symbol::from("show"),
             Value::Fn(GcRef::new(Fn::new_native(1, |vm, x| {
@@ -231,6 +611,166 @@ impl YexModule {
         Self::new(Symbol::from("Str"), methods)
     }
 
+    /// Creates a new Bytes type.
+    #[must_use]
+    pub fn bytes() -> Self {
+        let mut methods = EnvTable::new();
+
+        methods.insert(
+            Symbol::new("len"),
+            Value::Fn(GcRef::new(Fn::new_native(1, bytes::methods::len))),
+        );
+
+        methods.insert(
+            Symbol::new("get"),
+            Value::Fn(GcRef::new(Fn::new_native(2, bytes::methods::get))),
+        );
+
+        methods.insert(
+            Symbol::new("toStr"),
+            Value::Fn(GcRef::new(Fn::new_native(1, bytes::methods::to_str))),
+        );
+
+        methods.insert(
+            Symbol::new("read"),
+            Value::Fn(GcRef::new(Fn::new_native(1, bytes::methods::read))),
+        );
+
+        methods.insert(
+            Symbol::new("write"),
+            Value::Fn(GcRef::new(Fn::new_native(2, bytes::methods::write))),
+        );
+
+        methods.insert(
+            Symbol::from("show"),
+            Value::Fn(GcRef::new(Fn::new_native(1, |vm, x| {
+                super::show(vm, x).map(std::convert::Into::into)
+            }))),
+        );
+
+        Self::new(Symbol::from("Bytes"), methods)
+    }
+
+    /// Creates a new Struct type.
+    #[must_use]
+    pub fn r#struct() -> Self {
+        let mut methods = EnvTable::new();
+
+        methods.insert(
+            Symbol::from("get"),
+            Value::Fn(GcRef::new(Fn::new_native(2, r#struct::methods::get))),
+        );
+
+        methods.insert(
+            Symbol::from("has"),
+            Value::Fn(GcRef::new(Fn::new_native(2, r#struct::methods::has))),
+        );
+
+        methods.insert(
+            Symbol::from("show"),
+            Value::Fn(GcRef::new(Fn::new_native(1, |vm, x| {
+                super::show(vm, x).map(std::convert::Into::into)
+            }))),
+        );
+
+        Self::new(Symbol::from("Struct"), methods)
+    }
+
+    /// Creates a new Map type.
+    #[must_use]
+    pub fn map() -> Self {
+        let mut methods = EnvTable::new();
+
+        methods.insert(
+            Symbol::from("new"),
+            Value::Fn(GcRef::new(Fn::new_native(1, map::methods::new))),
+        );
+
+        methods.insert(
+            Symbol::from("fromList"),
+            Value::Fn(GcRef::new(Fn::new_native(1, map::methods::from_list))),
+        );
+
+        methods.insert(
+            Symbol::from("toList"),
+            Value::Fn(GcRef::new(Fn::new_native(1, map::methods::to_list))),
+        );
+
+        methods.insert(
+            Symbol::from("get"),
+            Value::Fn(GcRef::new(Fn::new_native(2, map::methods::get))),
+        );
+
+        methods.insert(
+            Symbol::from("has"),
+            Value::Fn(GcRef::new(Fn::new_native(2, map::methods::has))),
+        );
+
+        methods.insert(
+            Symbol::from("insert"),
+            Value::Fn(GcRef::new(Fn::new_native(3, map::methods::insert))),
+        );
+
+        methods.insert(
+            Symbol::from("remove"),
+            Value::Fn(GcRef::new(Fn::new_native(2, map::methods::remove))),
+        );
+
+        methods.insert(
+            Symbol::from("keys"),
+            Value::Fn(GcRef::new(Fn::new_native(1, map::methods::keys))),
+        );
+
+        methods.insert(
+            Symbol::from("values"),
+            Value::Fn(GcRef::new(Fn::new_native(1, map::methods::values))),
+        );
+
+        methods.insert(
+            Symbol::from("len"),
+            Value::Fn(GcRef::new(Fn::new_native(1, map::methods::len))),
+        );
+
+        methods.insert(
+            Symbol::from("show"),
+            Value::Fn(GcRef::new(Fn::new_native(1, |vm, x| {
+                super::show(vm, x).map(std::convert::Into::into)
+            }))),
+        );
+
+        Self::new(Symbol::from("Map"), methods)
+    }
+
+    /// Creates a new Channel type.
+    #[must_use]
+    pub fn channel() -> Self {
+        let mut methods = EnvTable::new();
+
+        methods.insert(
+            Symbol::new("new"),
+            Value::Fn(GcRef::new(Fn::new_native(1, channel::methods::new))),
+        );
+
+        methods.insert(
+            Symbol::new("send"),
+            Value::Fn(GcRef::new(Fn::new_native(2, channel::methods::send))),
+        );
+
+        methods.insert(
+            Symbol::new("recv"),
+            Value::Fn(GcRef::new(Fn::new_native(2, channel::methods::recv))),
+        );
+
+        methods.insert(
+            Symbol::from("show"),
+            Value::Fn(GcRef::new(Fn::new_native(1, |vm, x| {
+                super::show(vm, x).map(std::convert::Into::into)
+            }))),
+        );
+
+        Self::new(Symbol::from("Channel"), methods)
+    }
+
     /// Creates a new Bool type.
     #[must_use]
     pub fn bool() -> Self {
@@ -301,3 +841,16 @@ impl YexModule {
         Self::new(Symbol::from("Nil"), methods)
     }
 }
+
+#[test]
+fn modules_are_compared_by_name() {
+    let list = Value::Module(GcRef::new(YexModule::list()));
+
+    assert_eq!(list, Value::Module(GcRef::new(YexModule::list())));
+    assert_eq!(
+        list,
+        Value::Module(Value::List(crate::List::new()).type_of())
+    );
+    assert_ne!(list, Value::Module(GcRef::new(YexModule::num())));
+    assert_eq!(list.to_string(), "type 'List'");
+}
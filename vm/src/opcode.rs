@@ -21,10 +21,10 @@ pub enum OpCode {
     /// The stack layout after running it: [c, c]
     Dup,
 
-    /// Swaps two indexes on the stack
-    /// The stack layout before running this opcode: [c1, ..., c2]
-    /// The stack layout after running it: [c2, ..., c1]
-    Swap(usize, usize),
+    /// Duplicates the value below the top of the stack
+    /// The stack layout before running this opcode: [a, b]
+    /// The stack layout after running it: [a, b, a]
+    Over,
 
     /// Read a value from a variable, receives the index of the variable name in the constant table as
     /// argument
@@ -47,10 +47,10 @@ pub enum OpCode {
     /// The stack layout after running it: []
     Savg(Symbol),
 
-    /// Drops a variable, receives the index of the variable name in the constant table as argument
-    /// The stack layout before running this opcode: []
+    /// Save a value to a global variable, overwriting it if it already exists
+    /// The stack layout before running this opcode: [value-to-save]
     /// The stack layout after running it: []
-    Drop(usize),
+    Redefg(Symbol),
 
     /// Jump if the value on the stack top is false, receives the jump address as argument
     /// The stack layout before running this opcode: [cond]
@@ -83,8 +83,8 @@ pub enum OpCode {
     Rev,
 
     /// Reverse the order of the first N elements on the top of the stack
-    /// The stack layout before running this opcode: [a¹, a², ..., a']
-    /// The stack layout after running it: [b, a]
+    /// The stack layout before running this opcode: [a¹, a², ..., aⁿ]
+    /// The stack layout after running it: [aⁿ, ..., a², a¹]
     RevN(usize),
 
     /// Add the two values on the stack top
@@ -167,11 +167,6 @@ pub enum OpCode {
     /// The stack layout after running it: [result]
     LessEq,
 
-    /// Instantiates a new object
-    /// The stack layout before running this opcode: [type, ...args]
-    /// The stack layout after running it: [object]
-    New,
-
     /// Get the type of the value on the top of the stack
     /// The stack layout before running this opcode: [instance]
     /// The stack layout after running it: [type]
@@ -204,6 +199,26 @@ pub enum OpCode {
     /// The stack layout after running it: [tuple-element]
     TupGet(usize),
 
+    /// Creates a new `Struct`, receives the number of fields as argument
+    /// The stack layout before running this opcode: [...(value, name)]
+    /// The stack layout after running it: [struct]
+    Struct(usize),
+
+    /// Gets a field from a struct, raising a `FieldError` if it doesn't exist
+    /// The stack layout before running this opcode: [struct]
+    /// The stack layout after running it: [field-value]
+    Get(Symbol),
+
+    /// Copies a struct with one of its fields replaced, raising a `NameError` if it doesn't exist
+    /// The stack layout before running this opcode: [struct, value]
+    /// The stack layout after running it: [new-struct]
+    Set(Symbol),
+
+    /// Checks if the value on the top of the stack is a struct with the given field
+    /// The stack layout before running this opcode: [value]
+    /// The stack layout after running it: [bool]
+    Has(Symbol),
+
     /// Creates a Tagged value, receives the tag name as an argument
     /// The stack layout before running this opcode: [module, tuple]
     /// The stack layout after running it: [tagged]
@@ -0,0 +1,109 @@
+use crate::{error::InterpretResult, literal::TryGet, raise, List, Value};
+
+fn list_of<T: Into<Value>>(xs: Vec<T>) -> Value {
+    xs.into_iter()
+        .rev()
+        .map(Into::into)
+        .collect::<List>()
+        .into()
+}
+
+/// Parses CSV as described in RFC 4180, fields can be quoted to hold commas, quotes and line breaks
+pub fn csv_parse(args: &[Value]) -> InterpretResult<Value> {
+    let input: String = args[0].get()?;
+    let mut chars = input.chars().peekable();
+
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => raise!(ValueError, "Unclosed quoted CSV field")?,
+                }
+            },
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(list_of(std::mem::take(&mut row)));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(list_of(row));
+    }
+
+    Ok(list_of(rows))
+}
+
+fn encode_field(out: &mut String, field: &Value) {
+    let field = match field {
+        Value::Str(s) => s.to_string(),
+        other => other.to_string(),
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(&field);
+    }
+}
+
+/// Encodes a list of rows as CSV, quoting the fields that need it
+pub fn csv_encode(args: &[Value]) -> InterpretResult<Value> {
+    let rows: List = args[0].get()?;
+    let mut out = String::new();
+
+    for (idx, row) in rows.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+
+        let row: List = row.get()?;
+        for (idx, field) in row.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            encode_field(&mut out, &field);
+        }
+    }
+
+    Ok(out.into())
+}
+
+#[test]
+fn csv_quoted_fields() {
+    let input = "name,quote\r\n\"Doe, John\",\"said \"\"hi\"\"\nand left\"\n,\n";
+
+    let rows = csv_parse(&[input.to_string().into()]).unwrap();
+    let expected = list_of(vec![
+        list_of(vec!["name".to_string(), "quote".to_string()]),
+        list_of(vec![
+            "Doe, John".to_string(),
+            "said \"hi\"\nand left".to_string(),
+        ]),
+        list_of(vec![String::new(), String::new()]),
+    ]);
+    assert_eq!(rows, expected);
+
+    let encoded = csv_encode(&[rows]).unwrap();
+    let rows = csv_parse(&[encoded]).unwrap();
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn csv_unclosed_quote() {
+    let err = csv_parse(&["a,\"b".to_string().into()]).unwrap_err();
+    assert_eq!(err.err.as_str(), "ValueError");
+}
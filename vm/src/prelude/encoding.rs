@@ -0,0 +1,180 @@
+use crate::{error::InterpretResult, gc::GcRef, raise, Value};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Gets the raw bytes of a `Str` or `Bytes` value
+fn bytes_of(value: &Value) -> InterpretResult<Vec<u8>> {
+    match value {
+        Value::Str(s) => Ok(s.as_bytes().to_vec()),
+        Value::Bytes(b) => Ok(b.to_vec()),
+        other => raise!(
+            TypeError,
+            "Unexpected type '{}', expected type was 'Str' or 'Bytes'",
+            other.type_name()
+        ),
+    }
+}
+
+/// Decoded data is a `Str` when it's valid UTF-8, and `Bytes` otherwise
+fn decoded(bytes: Vec<u8>) -> Value {
+    match String::from_utf8(bytes) {
+        Ok(s) => s.into(),
+        Err(e) => Value::Bytes(GcRef::new(e.into_bytes())),
+    }
+}
+
+pub fn base64_encode(args: &[Value]) -> InterpretResult<Value> {
+    let bytes = bytes_of(&args[0])?;
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = match *chunk {
+            [a] => u32::from(a) << 16,
+            [a, b] => u32::from(a) << 16 | u32::from(b) << 8,
+            [a, b, c] => u32::from(a) << 16 | u32::from(b) << 8 | u32::from(c),
+            _ => unreachable!(),
+        };
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    Ok(out.into())
+}
+
+pub fn base64_decode(args: &[Value]) -> InterpretResult<Value> {
+    let input = bytes_of(&args[0])?;
+
+    if input.len() % 4 != 0 {
+        raise!(ValueError, "Invalid base64 length {}", input.len())?;
+    }
+
+    let padding = input.iter().rev().take_while(|&&c| c == b'=').count();
+    if padding > 2 {
+        raise!(ValueError, "Invalid base64 padding")?;
+    }
+
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+
+    for chunk in input[..input.len() - padding].chunks(4) {
+        let mut group = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            let digit = match BASE64.iter().position(|&d| d == c) {
+                Some(digit) => digit as u32,
+                None => raise!(ValueError, "Invalid base64 character '{}'", c as char)?,
+            };
+            group |= digit << (18 - 6 * i);
+        }
+
+        let bytes = group.to_be_bytes();
+        out.extend_from_slice(&bytes[1..chunk.len()]);
+    }
+
+    Ok(decoded(out))
+}
+
+pub fn hex_encode(args: &[Value]) -> InterpretResult<Value> {
+    let bytes = bytes_of(&args[0])?;
+    let mut out = String::with_capacity(bytes.len() * 2);
+
+    for byte in bytes {
+        out.push(HEX[usize::from(byte >> 4)] as char);
+        out.push(HEX[usize::from(byte & 0xf)] as char);
+    }
+
+    Ok(out.into())
+}
+
+pub fn hex_decode(args: &[Value]) -> InterpretResult<Value> {
+    let input = bytes_of(&args[0])?;
+
+    if input.len() % 2 != 0 {
+        raise!(ValueError, "Hex input must have an even length")?;
+    }
+
+    let mut out = Vec::with_capacity(input.len() / 2);
+
+    for pair in input.chunks(2) {
+        let mut byte = 0;
+        for &c in pair {
+            let digit = match (c as char).to_digit(16) {
+                Some(digit) => digit as u8,
+                None => raise!(ValueError, "Invalid hex digit '{}'", c as char)?,
+            };
+            byte = byte << 4 | digit;
+        }
+        out.push(byte);
+    }
+
+    Ok(decoded(out))
+}
+
+#[test]
+fn base64_round_trip() {
+    for (plain, encoded) in [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foobar", "Zm9vYmFy"),
+        ("héllo", "aMOpbGxv"),
+    ] {
+        let result = base64_encode(&[plain.to_string().into()]).unwrap();
+        assert_eq!(result, Value::from(encoded.to_string()));
+
+        let result = base64_decode(&[result]).unwrap();
+        assert_eq!(result, Value::from(plain.to_string()));
+    }
+}
+
+#[test]
+fn base64_malformed() {
+    for input in ["Zg=", "Zm9v!A==", "Z==="] {
+        let err = base64_decode(&[input.to_string().into()]).unwrap_err();
+        assert_eq!(err.err.as_str(), "ValueError", "{input}");
+    }
+}
+
+#[test]
+fn base64_binary_data() {
+    let result = base64_decode(&["/w==".to_string().into()]).unwrap();
+    assert_eq!(result, Value::Bytes(GcRef::new(vec![0xff])));
+
+    let result = base64_encode(&[result]).unwrap();
+    assert_eq!(result, Value::from("/w==".to_string()));
+}
+
+#[test]
+fn hex_round_trip() {
+    let result = hex_encode(&["héllo".to_string().into()]).unwrap();
+    assert_eq!(result, Value::from("68c3a96c6c6f".to_string()));
+
+    let result = hex_decode(&[result]).unwrap();
+    assert_eq!(result, Value::from("héllo".to_string()));
+
+    let result = hex_decode(&["48690A".to_string().into()]).unwrap();
+    assert_eq!(result, Value::from("Hi\n".to_string()));
+}
+
+#[test]
+fn hex_malformed() {
+    for input in ["abc", "zz"] {
+        let err = hex_decode(&[input.to_string().into()]).unwrap_err();
+        assert_eq!(err.err.as_str(), "ValueError", "{input}");
+    }
+}
+
+#[test]
+fn hex_binary_data() {
+    let result = hex_decode(&["ff00".to_string().into()]).unwrap();
+    assert_eq!(result, Value::Bytes(GcRef::new(vec![0xff, 0x00])));
+
+    let result = hex_encode(&[result]).unwrap();
+    assert_eq!(result, Value::from("ff00".to_string()));
+}
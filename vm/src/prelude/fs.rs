@@ -0,0 +1,195 @@
+use std::{fs, io};
+
+use crate::{
+    error::InterpretResult,
+    literal::{
+        nil,
+        r#struct::Struct,
+        result::{fail, ok},
+        TryGet,
+    },
+    List, Symbol, Value,
+};
+
+/// Wraps the outcome of a filesystem operation in a `Result.ok` or `Result.fail`
+fn result<T: Into<Value>>(res: io::Result<T>) -> Value {
+    match res {
+        Ok(value) => ok(vec![value.into()]),
+        Err(e) => fail(vec![e.to_string().into()]),
+    }
+}
+
+fn done(res: io::Result<()>) -> Value {
+    result(res.map(|()| nil()))
+}
+
+pub fn read_file(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    Ok(result(fs::read_to_string(path)))
+}
+
+pub fn write_file(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    let content: String = args[1].get()?;
+    Ok(done(fs::write(path, content)))
+}
+
+pub fn create_file(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    Ok(done(fs::File::create(path).map(drop)))
+}
+
+pub fn remove_file(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    Ok(done(fs::remove_file(path)))
+}
+
+pub fn exists_file(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    Ok(Value::Bool(fs::metadata(path).is_ok()))
+}
+
+/// Lists a directory as `%{name, isDir}` structs sorted by name, paths that exist but aren't
+/// directories give `nil`
+pub fn read_dir(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+
+    if fs::metadata(&path).is_ok_and(|meta| !meta.is_dir()) {
+        return Ok(nil());
+    }
+
+    let entries = fs::read_dir(path).and_then(|entries| {
+        let mut list = vec![];
+        for entry in entries {
+            let entry = entry?;
+            let fields = vec![
+                (
+                    Symbol::from("name"),
+                    entry.file_name().to_string_lossy().into_owned().into(),
+                ),
+                (Symbol::from("isDir"), entry.file_type()?.is_dir().into()),
+            ];
+            list.push(Value::Struct(Struct::from(fields)));
+        }
+        list.sort_by_key(ToString::to_string);
+
+        Ok(list.into_iter().rev().collect::<List>())
+    });
+
+    Ok(result(entries))
+}
+
+pub fn make_dir(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    Ok(done(fs::create_dir(path)))
+}
+
+pub fn remove_dir(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    Ok(done(fs::remove_dir(path)))
+}
+
+pub fn rename(args: &[Value]) -> InterpretResult<Value> {
+    let from: String = args[0].get()?;
+    let to: String = args[1].get()?;
+    Ok(done(fs::rename(from, to)))
+}
+
+#[cfg(test)]
+fn tag_of(value: &Value) -> &str {
+    match value {
+        Value::Tagged(_, tag, _) => tag.as_str(),
+        other => panic!("expected a tagged value, found {other}"),
+    }
+}
+
+#[test]
+fn file_round_trip() {
+    let dir = std::env::temp_dir().join(format!("yex-fs-{}", std::process::id()));
+    let dir_path: Value = dir.to_string_lossy().into_owned().into();
+    let file_path: Value = dir.join("file.txt").to_string_lossy().into_owned().into();
+
+    assert_eq!(
+        tag_of(&make_dir(std::slice::from_ref(&dir_path)).unwrap()),
+        "Result.ok"
+    );
+
+    let content: Value = "hello\nyex".to_string().into();
+    let written = write_file(&[file_path.clone(), content.clone()]).unwrap();
+    assert_eq!(tag_of(&written), "Result.ok");
+    assert_eq!(
+        exists_file(std::slice::from_ref(&file_path)).unwrap(),
+        Value::Bool(true)
+    );
+
+    let read = read_file(std::slice::from_ref(&file_path)).unwrap();
+    assert_eq!(read, ok(vec![content]));
+
+    let entries = read_dir(std::slice::from_ref(&dir_path)).unwrap();
+    let entry = Struct::from(vec![
+        (Symbol::from("name"), "file.txt".to_string().into()),
+        (Symbol::from("isDir"), false.into()),
+    ]);
+    let expected: List = std::iter::once(Value::Struct(entry)).collect();
+    assert_eq!(entries, ok(vec![expected.into()]));
+    assert_eq!(read_dir(std::slice::from_ref(&file_path)).unwrap(), nil());
+
+    assert_eq!(
+        tag_of(&remove_file(std::slice::from_ref(&file_path)).unwrap()),
+        "Result.ok"
+    );
+    assert_eq!(
+        exists_file(std::slice::from_ref(&file_path)).unwrap(),
+        Value::Bool(false)
+    );
+    assert_eq!(tag_of(&read_file(&[file_path]).unwrap()), "Result.fail");
+
+    assert_eq!(
+        tag_of(&remove_dir(std::slice::from_ref(&dir_path)).unwrap()),
+        "Result.ok"
+    );
+    assert_eq!(tag_of(&read_dir(&[dir_path]).unwrap()), "Result.fail");
+}
+
+#[test]
+fn dirs_and_renames() {
+    let dir = std::env::temp_dir().join(format!("yex-rename-{}", std::process::id()));
+    let path = |name: &str| -> Value { dir.join(name).to_string_lossy().into_owned().into() };
+    let dir_path: Value = dir.to_string_lossy().into_owned().into();
+
+    assert_eq!(
+        make_dir(std::slice::from_ref(&dir_path)).unwrap(),
+        ok(vec![nil()])
+    );
+    assert_eq!(
+        tag_of(&make_dir(std::slice::from_ref(&dir_path)).unwrap()),
+        "Result.fail"
+    );
+
+    write_file(&[path("a.txt"), "a".to_string().into()]).unwrap();
+    assert_eq!(
+        rename(&[path("a.txt"), path("b.txt")]).unwrap(),
+        ok(vec![nil()])
+    );
+    assert_eq!(exists_file(&[path("a.txt")]).unwrap(), Value::Bool(false));
+    assert_eq!(
+        read_file(&[path("b.txt")]).unwrap(),
+        ok(vec!["a".to_string().into()])
+    );
+    assert_eq!(
+        tag_of(&rename(&[path("a.txt"), path("c.txt")]).unwrap()),
+        "Result.fail"
+    );
+
+    // only empty directories can be removed
+    assert_eq!(
+        tag_of(&remove_dir(std::slice::from_ref(&dir_path)).unwrap()),
+        "Result.fail"
+    );
+    remove_file(&[path("b.txt")]).unwrap();
+    assert_eq!(
+        remove_dir(std::slice::from_ref(&dir_path)).unwrap(),
+        ok(vec![nil()])
+    );
+    assert_eq!(exists_file(&[dir_path]).unwrap(), Value::Bool(false));
+}
@@ -0,0 +1,484 @@
+use std::{fmt::Write, iter::Peekable, str::Chars};
+
+use crate::{
+    error::InterpretResult,
+    literal::{
+        r#struct::Struct,
+        result::{fail, ok},
+        TryGet,
+    },
+    raise, List, Symbol, Value,
+};
+
+fn write_str(out: &mut String, str: &str) {
+    out.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Writes values as JSON, pretty output puts every element on its own indented line
+struct Encoder {
+    out: String,
+    pretty: bool,
+    depth: usize,
+}
+
+impl Encoder {
+    fn new(pretty: bool) -> Self {
+        Self {
+            out: String::new(),
+            pretty,
+            depth: 0,
+        }
+    }
+
+    fn value(&mut self, value: &Value) -> InterpretResult<()> {
+        match value {
+            Value::Nil => self.out.push_str("null"),
+            Value::Bool(b) => self.out.push_str(if *b { "true" } else { "false" }),
+            Value::Num(n) if n.is_finite() => self.out.push_str(&n.to_string()),
+            Value::Num(n) => raise!(ValueError, "Can't serialize {} to JSON", n)?,
+            Value::Str(s) => write_str(&mut self.out, s),
+            Value::Sym(s) => write_str(&mut self.out, s.as_str()),
+            Value::List(xs) => self.collection('[', ']', xs.iter().map(|x| (None, x)))?,
+            Value::Tuple(xs) => {
+                self.collection('[', ']', xs.0.iter().map(|x| (None, x.clone())))?;
+            }
+            Value::Struct(fields) => self.collection(
+                '{',
+                '}',
+                fields.0.iter().map(|(name, x)| (Some(*name), x.clone())),
+            )?,
+            other => raise!(
+                TypeError,
+                "Values of type '{}' can't be serialized to JSON",
+                other.type_name()
+            )?,
+        }
+
+        Ok(())
+    }
+
+    fn newline(&mut self) {
+        if self.pretty {
+            self.out.push('\n');
+            self.out.push_str(&"  ".repeat(self.depth));
+        }
+    }
+
+    /// Writes an array, or an object if the elements are named
+    fn collection(
+        &mut self,
+        open: char,
+        close: char,
+        xs: impl Iterator<Item = (Option<Symbol>, Value)>,
+    ) -> InterpretResult<()> {
+        if self.depth >= MAX_DEPTH {
+            return raise!(
+                ValueError,
+                "Can't serialize values nested deeper than {} levels",
+                MAX_DEPTH
+            );
+        }
+
+        self.out.push(open);
+        self.depth += 1;
+
+        let mut empty = true;
+        for (name, x) in xs {
+            if !empty {
+                self.out.push(',');
+            }
+            empty = false;
+            self.newline();
+
+            if let Some(name) = name {
+                write_str(&mut self.out, name.as_str());
+                self.out.push_str(if self.pretty { ": " } else { ":" });
+            }
+            self.value(&x)?;
+        }
+
+        self.depth -= 1;
+        if !empty {
+            self.newline();
+        }
+        self.out.push(close);
+        Ok(())
+    }
+}
+
+/// How many arrays and objects can be nested, so deep input or values can't overflow the native
+/// stack
+const MAX_DEPTH: usize = 512;
+
+/// A recursive descent JSON parser, errors are plain messages that end up inside `Result.fail`
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    depth: usize,
+}
+
+type Parsed<T> = Result<T, String>;
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().peekable(),
+            depth: 0,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Parsed<()> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected '{expected}', found '{c}'")),
+            None => Err(format!("Expected '{expected}', found end of input")),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Parsed<Value> {
+        for c in word.chars() {
+            self.expect(c)?;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Parsed<Value> {
+        self.skip_whitespace();
+        let value = match self.chars.peek() {
+            Some('n') => self.keyword("null", Value::Nil)?,
+            Some('t') => self.keyword("true", Value::Bool(true))?,
+            Some('f') => self.keyword("false", Value::Bool(false))?,
+            Some('"') => self.string()?.into(),
+            Some('[') => self.nested(Self::array)?,
+            Some('{') => self.nested(Self::object)?,
+            Some(c) if *c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(c) => return Err(format!("Unexpected character '{c}'")),
+            None => return Err("Unexpected end of input".to_string()),
+        };
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Parsed<Value>) -> Parsed<Value> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("Nesting deeper than {MAX_DEPTH} levels"));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn number(&mut self) -> Parsed<Value> {
+        let mut number = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(c);
+        }
+
+        number
+            .parse()
+            .map(Value::Num)
+            .map_err(|_| format!("Invalid number '{number}'"))
+    }
+
+    fn string(&mut self) -> Parsed<String> {
+        self.expect('"')?;
+        let mut str = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(str),
+                Some('\\') => str.push(self.escape()?),
+                Some(c) => str.push(c),
+                None => return Err("Unclosed string".to_string()),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Parsed<char> {
+        let c = match self.chars.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => self.unicode_escape()?,
+            Some(c) => return Err(format!("Unknown escape char '{c}'")),
+            None => return Err("Unclosed string".to_string()),
+        };
+        Ok(c)
+    }
+
+    /// Reads the digits of a `\\u` escape, combining a surrogate pair into one char
+    fn unicode_escape(&mut self) -> Parsed<char> {
+        let high = self.hex()?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return char::from_u32(high)
+                .ok_or_else(|| format!("Invalid unicode escape '\\u{high:04x}'"));
+        }
+
+        if self.chars.next_if_eq(&'\\').is_none() || self.chars.next_if_eq(&'u').is_none() {
+            return Err(format!("Unpaired surrogate '\\u{high:04x}'"));
+        }
+
+        let low = self.hex()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(format!("Unpaired surrogate '\\u{high:04x}'"));
+        }
+
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+            .ok_or_else(|| format!("Invalid unicode escape '\\u{high:04x}\\u{low:04x}'"))
+    }
+
+    fn hex(&mut self) -> Parsed<u32> {
+        let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+        u32::from_str_radix(&hex, 16).map_err(|_| format!("Invalid unicode escape '\\u{hex}'"))
+    }
+
+    fn array(&mut self) -> Parsed<Value> {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        let mut xs = vec![];
+        if self.chars.next_if_eq(&']').is_none() {
+            loop {
+                xs.push(self.value()?);
+                if self.chars.next_if_eq(&']').is_some() {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        Ok(xs.into_iter().rev().collect::<List>().into())
+    }
+
+    fn object(&mut self) -> Parsed<Value> {
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        let mut fields = vec![];
+        if self.chars.next_if_eq(&'}').is_none() {
+            loop {
+                self.skip_whitespace();
+                let name = self.string()?;
+                self.skip_whitespace();
+                self.expect(':')?;
+                fields.push((Symbol::new(name), self.value()?));
+
+                if self.chars.next_if_eq(&'}').is_some() {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        Ok(Value::Struct(Struct::from(fields)))
+    }
+}
+
+fn encode(value: &Value, pretty: bool) -> InterpretResult<Value> {
+    let mut encoder = Encoder::new(pretty);
+    encoder.value(value)?;
+    Ok(encoder.out.into())
+}
+
+pub fn to_json(args: &[Value]) -> InterpretResult<Value> {
+    encode(&args[0], false)
+}
+
+pub fn json_stringify_with(args: &[Value]) -> InterpretResult<Value> {
+    let pretty: bool = args[0].get()?;
+    encode(&args[1], pretty)
+}
+
+/// Parses a whole JSON document, errors say where the parser stopped
+fn parse(input: &str) -> Parsed<Value> {
+    let mut parser = Parser::new(input);
+
+    let value = parser.value().and_then(|value| match parser.chars.next() {
+        Some(c) => Err(format!("Unexpected trailing character '{c}'")),
+        None => Ok(value),
+    });
+
+    value.map_err(|msg| {
+        let consumed = input.chars().count() - parser.chars.count();
+        let (line, column) =
+            input
+                .chars()
+                .take(consumed)
+                .fold((1, 1), |(line, column), c| match c {
+                    '\n' => (line + 1, 1),
+                    _ => (line, column + 1),
+                });
+
+        format!("{msg} at {line}:{column}")
+    })
+}
+
+pub fn from_json(args: &[Value]) -> InterpretResult<Value> {
+    let input: String = args[0].get()?;
+
+    Ok(match parse(&input) {
+        Ok(value) => ok(vec![value]),
+        Err(msg) => fail(vec![msg.into()]),
+    })
+}
+
+pub fn json_parse(args: &[Value]) -> InterpretResult<Value> {
+    let input: String = args[0].get()?;
+
+    match parse(&input) {
+        Ok(value) => Ok(value),
+        Err(msg) => raise!(ValueError, "{}", msg),
+    }
+}
+
+#[test]
+fn json_round_trip() {
+    let input = r#"{"name":"yex","tags":["a","b\n"],"nested":{"n":-1.5,"ok":true,"none":null}}"#;
+
+    let parsed = from_json(&[input.to_string().into()]).unwrap();
+    let value = match parsed {
+        Value::Tagged(_, tag, tup) if tag.as_str() == "Result.ok" => tup.0[0].clone(),
+        other => panic!("expected Result.ok, found {other}"),
+    };
+
+    assert_eq!(to_json(&[value]).unwrap(), Value::from(input.to_string()));
+}
+
+#[test]
+fn json_malformed() {
+    for input in ["", "[1,", r#"{"a" 1}"#, "nul", r#""abc"#, "1 2"] {
+        let parsed = from_json(&[input.to_string().into()]).unwrap();
+        assert!(
+            matches!(parsed, Value::Tagged(_, tag, _) if tag.as_str() == "Result.fail"),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn json_parse_nested_values() {
+    let parsed = json_parse(&[r#"{"a": {"b": [1, "two", null, false]}}"#.to_string().into()]);
+
+    let list: List = vec![
+        Value::Num(1.0),
+        "two".to_string().into(),
+        Value::Nil,
+        Value::Bool(false),
+    ]
+    .into_iter()
+    .rev()
+    .collect();
+    let b = Struct::from(vec![(Symbol::new("b"), list.into())]);
+    let a = Struct::from(vec![(Symbol::new("a"), Value::Struct(b))]);
+
+    assert_eq!(parsed.unwrap(), Value::Struct(a));
+}
+
+#[test]
+fn json_parse_errors_are_located() {
+    let err = json_parse(&["[1,\n  x]".to_string().into()]).unwrap_err();
+    assert_eq!(err.err.as_str(), "ValueError");
+    assert_eq!(err.msg, "Unexpected character 'x' at 2:3");
+}
+
+#[test]
+fn json_stringify_escapes_and_round_trips() {
+    let value = Value::Struct(Struct::from(vec![
+        (Symbol::new("quote"), "say \"hi\"\n".to_string().into()),
+        (
+            Symbol::new("xs"),
+            Value::Tuple(vec![Value::Num(1.0), Value::Nil].into()),
+        ),
+    ]));
+
+    let json = to_json(std::slice::from_ref(&value)).unwrap();
+    assert_eq!(
+        json,
+        Value::from(r#"{"quote":"say \"hi\"\n","xs":[1,null]}"#.to_string())
+    );
+
+    let pretty = json_stringify_with(&[Value::Bool(true), value]).unwrap();
+    assert_eq!(
+        pretty,
+        Value::from(
+            "{\n  \"quote\": \"say \\\"hi\\\"\\n\",\n  \"xs\": [\n    1,\n    null\n  ]\n}"
+                .to_string()
+        )
+    );
+
+    // tuples come back as lists
+    let list: List = vec![Value::Num(1.0), Value::Nil]
+        .into_iter()
+        .rev()
+        .collect();
+    let parsed = json_parse(&[pretty]).unwrap();
+    assert_eq!(
+        parsed,
+        Value::Struct(Struct::from(vec![
+            (Symbol::new("quote"), "say \"hi\"\n".to_string().into()),
+            (Symbol::new("xs"), list.into()),
+        ]))
+    );
+}
+
+#[test]
+fn json_surrogate_pairs() {
+    let parsed = json_parse(&[r#""\ud83d\ude00 \u00e9""#.to_string().into()]).unwrap();
+    assert_eq!(parsed, Value::from("\u{1F600} \u{e9}".to_string()));
+
+    for input in [r#""\ud83d""#, r#""\ud83d\u0041""#, r#""\ude00""#] {
+        let err = json_parse(&[input.to_string().into()]).unwrap_err();
+        assert_eq!(err.err.as_str(), "ValueError", "{input}");
+    }
+}
+
+#[test]
+fn json_nesting_is_limited() {
+    let deep = "[".repeat(100_000);
+    let err = json_parse(&[deep.into()]).unwrap_err();
+    assert_eq!(err.msg, "Nesting deeper than 512 levels at 1:513");
+
+    let nested = format!("{}{}", "[".repeat(512), "]".repeat(512));
+    assert!(json_parse(&[nested.into()]).is_ok());
+}
+
+#[test]
+fn json_encoding_depth_is_limited() {
+    let nest = |depth| {
+        (0..depth).fold(Value::Nil, |inner, _| {
+            Value::List(List::new().prepend(inner))
+        })
+    };
+
+    let err = to_json(&[nest(1000)]).unwrap_err();
+    assert_eq!(err.err.as_str(), "ValueError");
+
+    assert!(to_json(&[nest(512)]).is_ok());
+}
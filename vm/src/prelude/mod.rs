@@ -1,11 +1,24 @@
+mod csv;
+mod encoding;
+mod fs;
+pub(crate) mod json;
+pub(crate) mod random;
+mod system;
+pub(crate) mod task;
+
 use crate::{
     env::EnvTable,
     error::InterpretError,
     gc::GcRef,
     literal::{nil, show, TryGet, Value},
-    raise_err, InterpretResult, Symbol, VirtualMachine, YexModule,
+    raise_err, InterpretResult, List, Symbol, VirtualMachine, YexModule,
+};
+use std::{
+    cmp::Ordering,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
-use std::io::{self, Write};
 
 #[macro_export]
 /// Insert a function into a `EnvTable`
@@ -49,12 +62,14 @@ macro_rules! insert {
 }
 
 fn println(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
-    println!("{}", show(vm, args.into())?);
+    let str = show(vm, args.into())?;
+    writeln!(vm.output, "{str}")?;
     Ok(nil())
 }
 
 fn print(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
-    print!("{}", show(vm, args.into())?);
+    let str = show(vm, args.into())?;
+    write!(vm.output, "{str}")?;
     Ok(nil())
 }
 
@@ -63,17 +78,25 @@ fn debug_stack(vm: &mut VirtualMachine, _args: &[Value]) -> InterpretResult<Valu
     Ok(nil())
 }
 
-fn input(args: &[Value]) -> InterpretResult<Value> {
+fn input(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
     let prompt: String = args[0].get()?;
-    print!("{}", prompt);
+    write!(vm.output, "{prompt}")?;
 
-    io::stdout().flush()?;
+    vm.output.flush()?;
 
     let mut input = String::new();
 
-    io::stdin().read_line(&mut input)?;
+    match &mut vm.input {
+        Some(source) => source.read_line(&mut input)?,
+        None => std::io::stdin().read_line(&mut input)?,
+    };
 
-    input.pop();
+    if input.ends_with('\n') {
+        input.pop();
+        if input.ends_with('\r') {
+            input.pop();
+        }
+    }
 
     Ok(Value::Str(GcRef::new(input)))
 }
@@ -82,16 +105,97 @@ fn r#type(args: &[Value]) -> InterpretResult<Value> {
     Ok(Value::Module(args[0].type_of()))
 }
 
+/// Converts any value to a string, string interpolations call it on every spliced expression
+fn show_value(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
+    Ok(show(vm, args.into())?.into())
+}
+
+/// The function form of `#`, so it can be used in pipes
+fn length(args: &[Value]) -> InterpretResult<Value> {
+    Ok(Value::Num(args[0].len()? as f64))
+}
+
+/// Writes a value as yex source, numbers, strings, symbols, booleans, nil and lists, tuples and
+/// structs of them read back as the same value
 fn inspect(args: &[Value]) -> InterpretResult<Value> {
-    Ok(Value::Str(GcRef::new(format!("{:#?}", &args[0]))))
+    Ok(Value::Str(GcRef::new(args[0].to_string())))
 }
 
+/// Parses a number, `inf` and `nan` are rejected even though Rust accepts them, since they can't be
+/// written as literals either
 fn num(args: &[Value]) -> InterpretResult<Value> {
     let str: String = args[0].get()?;
 
-    str.parse::<f64>()
-        .map(Value::Num)
-        .map_err(|_| raise_err!(TypeError, "Cannot convert '{}' to number", str))
+    match str.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok(Value::Num(n)),
+        _ => Err(raise_err!(TypeError, "Cannot convert '{}' to number", str)),
+    }
+}
+
+fn min(args: &[Value]) -> InterpretResult<Value> {
+    match args[1].ord_cmp(&args[0])? {
+        Ordering::Less => Ok(args[1].clone()),
+        _ => Ok(args[0].clone()),
+    }
+}
+
+fn max(args: &[Value]) -> InterpretResult<Value> {
+    match args[1].ord_cmp(&args[0])? {
+        Ordering::Greater => Ok(args[1].clone()),
+        _ => Ok(args[0].clone()),
+    }
+}
+
+fn clamp(args: &[Value]) -> InterpretResult<Value> {
+    let (value, low, high) = (&args[0], &args[1], &args[2]);
+
+    if low.ord_cmp(high)? == Ordering::Greater {
+        return Err(raise_err!(
+            ValueError,
+            "Invalid clamp bounds, '{}' is greater than '{}'",
+            low,
+            high
+        ));
+    }
+
+    if value.ord_cmp(low)? == Ordering::Less {
+        Ok(low.clone())
+    } else if value.ord_cmp(high)? == Ordering::Greater {
+        Ok(high.clone())
+    } else {
+        Ok(value.clone())
+    }
+}
+
+fn range_of(start: isize, end: isize, step: isize) -> InterpretResult<Value> {
+    if step == 0 {
+        return Err(raise_err!(ValueError, "Range step can't be zero"));
+    }
+
+    let mut xs = vec![];
+    let mut n = start;
+    while (step > 0 && n < end) || (step < 0 && n > end) {
+        xs.push(Value::Num(n as f64));
+        n += step;
+    }
+
+    Ok(xs.into_iter().rev().collect::<List>().into())
+}
+
+fn approx_eq(args: &[Value]) -> InterpretResult<Value> {
+    let a: f64 = args[0].get()?;
+    let b: f64 = args[1].get()?;
+    let epsilon: f64 = args[2].get()?;
+
+    Ok(((a - b).abs() <= epsilon).into())
+}
+
+fn range(args: &[Value]) -> InterpretResult<Value> {
+    range_of(args[0].get()?, args[1].get()?, 1)
+}
+
+fn range_step(args: &[Value]) -> InterpretResult<Value> {
+    range_of(args[0].get()?, args[1].get()?, args[2].get()?)
 }
 
 fn exit(args: &[Value]) -> InterpretResult<Value> {
@@ -100,16 +204,120 @@ fn exit(args: &[Value]) -> InterpretResult<Value> {
     std::process::exit(code as i32);
 }
 
+fn getenv(args: &[Value]) -> InterpretResult<Value> {
+    let name: String = args[0].get()?;
+
+    Ok(std::env::var(name).map_or_else(|_| nil(), Into::into))
+}
+
+fn setenv(args: &[Value]) -> InterpretResult<Value> {
+    let name: String = args[0].get()?;
+    let value: String = args[1].get()?;
+
+    if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+        return Err(raise_err!(
+            ValueError,
+            "Invalid environment variable '{}'",
+            name
+        ));
+    }
+
+    std::env::set_var(name, value);
+    Ok(nil())
+}
+
+fn getargs(_: &[Value]) -> InterpretResult<Value> {
+    let args: List = std::env::args().rev().map(Into::into).collect();
+    Ok(args.into())
+}
+
+fn path_join(args: &[Value]) -> InterpretResult<Value> {
+    let segments: List = args[0].get()?;
+
+    let mut path = PathBuf::new();
+    for segment in segments.iter() {
+        let segment: String = segment.get()?;
+        path.push(segment);
+    }
+
+    Ok(path.to_string_lossy().into_owned().into())
+}
+
+/// The last component of a path, or `""` if it has none
+fn basename(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    let name = Path::new(&path).file_name().unwrap_or_default();
+
+    Ok(name.to_string_lossy().into_owned().into())
+}
+
+/// Everything before the last component, `""` for a bare name
+fn dirname(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    let parent = Path::new(&path).parent().unwrap_or_else(|| Path::new(""));
+
+    Ok(parent.to_string_lossy().into_owned().into())
+}
+
+/// The extension including its dot, like `".txt"`, or `""` if there is none
+fn extname(args: &[Value]) -> InterpretResult<Value> {
+    let path: String = args[0].get()?;
+    let ext = Path::new(&path)
+        .extension()
+        .map_or_else(String::new, |ext| format!(".{}", ext.to_string_lossy()));
+
+    Ok(ext.into())
+}
+
+fn now(_: &[Value]) -> InterpretResult<Value> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| raise_err!(ValueError, "{}", e))?;
+
+    Ok(Value::Num(elapsed.as_secs_f64()))
+}
+
+/// Seconds since the VM was created, unlike `now` it never goes backwards
+fn monotonic(vm: &mut VirtualMachine, _: &[Value]) -> InterpretResult<Value> {
+    Ok(Value::Num(vm.started.elapsed().as_secs_f64()))
+}
+
 fn raise(args: &[Value]) -> InterpretResult<Value> {
     let err: Symbol = args[0].get()?;
     let msg: String = args[1].get()?;
 
-    Err(InterpretError {
-        err,
-        msg,
-        line: unsafe { crate::LINE },
-        column: unsafe { crate::COLUMN },
-    })
+    Err(InterpretError::new(err, msg))
+}
+
+/// Raises a `Thrown` error whose rescued value is the argument itself
+fn throw(args: &[Value]) -> InterpretResult<Value> {
+    Err(InterpretError::thrown(args[0].clone()))
+}
+
+/// Returns nil on truthy values, raises an `AssertionError` otherwise
+fn assert(args: &[Value]) -> InterpretResult<Value> {
+    if args[0].to_bool() {
+        return Ok(nil());
+    }
+
+    Err(raise_err!(
+        AssertionError,
+        "Assertion failed, '{}' is falsy",
+        args[0]
+    ))
+}
+
+fn assert_eq(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
+    if args[0] == args[1] {
+        return Ok(nil());
+    }
+
+    Err(raise_err!(
+        AssertionError,
+        "Assertion failed, '{}' is not equal to '{}'",
+        show(vm, vec![args[0].clone()])?,
+        show(vm, vec![args[1].clone()])?
+    ))
 }
 
 pub fn prelude() -> EnvTable {
@@ -117,12 +325,61 @@ pub fn prelude() -> EnvTable {
     insert_fn!(:vm prelude, "println", println, 1);
     insert_fn!(:vm prelude, "print", print, 1);
     insert_fn!(:vm prelude, "print_stack!", debug_stack, 1);
-    insert_fn!(prelude, "input", input);
+    insert_fn!(:vm prelude, "input", input, 1);
     insert_fn!(prelude, "type", r#type);
     insert_fn!(prelude, "inspect", inspect);
+    insert_fn!(:vm prelude, "show", show_value, 1);
+    insert_fn!(prelude, "length", length);
     insert_fn!(prelude, "num", num);
+    insert_fn!(prelude, "min", min, 2);
+    insert_fn!(prelude, "max", max, 2);
+    insert_fn!(prelude, "clamp", clamp, 3);
+    insert_fn!(prelude, "approxEq", approx_eq, 3);
+    insert_fn!(prelude, "range", range, 2);
+    insert_fn!(prelude, "range_step", range_step, 3);
+    insert_fn!(prelude, "base64Encode", encoding::base64_encode);
+    insert_fn!(prelude, "base64Decode", encoding::base64_decode);
+    insert_fn!(prelude, "hexEncode", encoding::hex_encode);
+    insert_fn!(prelude, "hexDecode", encoding::hex_decode);
+    insert_fn!(prelude, "toJson", json::to_json);
+    insert_fn!(prelude, "fromJson", json::from_json);
+    insert_fn!(prelude, "json_parse", json::json_parse);
+    insert_fn!(prelude, "json_stringify", json::to_json);
+    insert_fn!(prelude, "json_stringify_with", json::json_stringify_with, 2);
+    insert_fn!(prelude, "csvParse", csv::csv_parse);
+    insert_fn!(prelude, "csvEncode", csv::csv_encode);
+    insert_fn!(prelude, "readFile", fs::read_file);
+    insert_fn!(prelude, "writeFile", fs::write_file, 2);
+    insert_fn!(prelude, "createFile", fs::create_file);
+    insert_fn!(prelude, "removeFile", fs::remove_file);
+    insert_fn!(prelude, "existsFile", fs::exists_file);
+    insert_fn!(prelude, "readDir", fs::read_dir);
+    insert_fn!(prelude, "makeDir", fs::make_dir);
+    insert_fn!(prelude, "removeDir", fs::remove_dir);
+    insert_fn!(prelude, "mkdir", fs::make_dir);
+    insert_fn!(prelude, "rmdir", fs::remove_dir);
+    insert_fn!(prelude, "rename", fs::rename, 2);
+    insert_fn!(prelude, "pathJoin", path_join);
+    insert_fn!(prelude, "basename", basename);
+    insert_fn!(prelude, "dirname", dirname);
+    insert_fn!(prelude, "extname", extname);
+    insert_fn!(:vm prelude, "spawn", task::spawn, 1);
+    insert_fn!(:vm prelude, "yield", task::r#yield, 1);
+    insert_fn!(:vm prelude, "runTasks", task::run_tasks, 1);
+    insert_fn!(prelude, "system", system::system, 2);
+    insert_fn!(prelude, "getenv", getenv);
+    insert_fn!(prelude, "setenv", setenv, 2);
+    insert_fn!(prelude, "getargs", getargs);
+    insert_fn!(prelude, "now", now);
+    insert_fn!(:vm prelude, "monotonic", monotonic, 1);
+    insert_fn!(:vm prelude, "random", random::random, 1);
+    insert_fn!(:vm prelude, "random_int", random::random_int, 2);
+    insert_fn!(:vm prelude, "seed", random::seed, 1);
     insert_fn!(prelude, "exit", exit);
     insert_fn!(prelude, "raise", raise, 2);
+    insert_fn!(prelude, "throw", throw);
+    insert_fn!(prelude, "assert", assert);
+    insert_fn!(:vm prelude, "assert_eq", assert_eq, 2);
 
     insert!(prelude, "Nil", Value::Module(GcRef::new(YexModule::nil())));
     insert!(
@@ -132,6 +389,11 @@ pub fn prelude() -> EnvTable {
     );
     insert!(prelude, "Num", Value::Module(GcRef::new(YexModule::num())));
     insert!(prelude, "Str", Value::Module(GcRef::new(YexModule::str())));
+    insert!(
+        prelude,
+        "Bytes",
+        Value::Module(GcRef::new(YexModule::bytes()))
+    );
     insert!(
         prelude,
         "List",
@@ -149,6 +411,17 @@ pub fn prelude() -> EnvTable {
         "Result",
         Value::Module(GcRef::new(YexModule::result()))
     );
+    insert!(
+        prelude,
+        "Channel",
+        Value::Module(GcRef::new(YexModule::channel()))
+    );
+    insert!(prelude, "Map", Value::Module(GcRef::new(YexModule::map())));
+    insert!(
+        prelude,
+        "Struct",
+        Value::Module(GcRef::new(YexModule::r#struct()))
+    );
     insert!(prelude, "FFI", Value::Module(GcRef::new(YexModule::ffi())));
 
     prelude
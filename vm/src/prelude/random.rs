@@ -0,0 +1,99 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{error::InterpretResult, literal::TryGet, raise, Value, VirtualMachine};
+
+/// A xorshift64* generator, good enough for scripts and reproducible with `seed`
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        const MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+
+        // xorshift gets stuck on a zero state
+        match seed ^ MIX {
+            0 => Self(MIX),
+            state => Self(state),
+        }
+    }
+
+    /// Seeds the generator from the system clock
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// An index in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `[0, 1)`, built from the top 53 bits so every value is exact
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub fn random(vm: &mut VirtualMachine, _: &[Value]) -> InterpretResult<Value> {
+    Ok(Value::Num(vm.rng.next_f64()))
+}
+
+/// A random integer between `start` and `end`, both inclusive
+pub fn random_int(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
+    let start: isize = args[0].get()?;
+    let end: isize = args[1].get()?;
+
+    if start > end {
+        raise!(ValueError, "Empty range {}..{}", start, end)?;
+    }
+
+    // the full isize range has 2^64 values, one too many for a u64
+    let span = end.abs_diff(start) as u128 + 1;
+    let n = start.wrapping_add_unsigned((u128::from(vm.rng.next_u64()) % span) as usize);
+    Ok(Value::Num(n as f64))
+}
+
+pub fn seed(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
+    let seed: isize = args[0].get()?;
+    vm.rng = Rng::new(seed as u64);
+    Ok(Value::Nil)
+}
+
+#[test]
+fn same_seed_same_sequence() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    let mut c = Rng::new(43);
+
+    let xs: Vec<_> = (0..16).map(|_| a.next_u64()).collect();
+    let ys: Vec<_> = (0..16).map(|_| b.next_u64()).collect();
+    let zs: Vec<_> = (0..16).map(|_| c.next_u64()).collect();
+
+    assert_eq!(xs, ys);
+    assert_ne!(xs, zs);
+    assert!((0..1000).all(|_| (0.0..1.0).contains(&a.next_f64())));
+}
+
+#[test]
+fn random_int_whole_range() {
+    let mut vm = VirtualMachine::default();
+    let args = [Value::Num(isize::MIN as f64), Value::Num(isize::MAX as f64)];
+
+    for _ in 0..100 {
+        assert!(matches!(random_int(&mut vm, &args), Ok(Value::Num(_))));
+    }
+
+    let args = [Value::Num(-3.0), Value::Num(3.0)];
+    for _ in 0..100 {
+        let n: isize = random_int(&mut vm, &args).unwrap().get().unwrap();
+        assert!((-3..=3).contains(&n));
+    }
+}
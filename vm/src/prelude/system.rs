@@ -0,0 +1,64 @@
+use std::process::Command;
+
+use crate::{
+    error::InterpretResult,
+    literal::{
+        nil,
+        result::{fail, ok},
+        TryGet,
+    },
+    List, Symbol, Tuple, Value,
+};
+
+/// Runs a command until it exits, returning `Result.ok (stdout, stderr, exit-code)`. The exit code
+/// is `nil` if the process was killed by a signal
+pub fn system(args: &[Value]) -> InterpretResult<Value> {
+    let program: String = args[0].get()?;
+    let list: List = args[1].get()?;
+
+    let mut cmd_args = vec![];
+    for arg in list.iter() {
+        let arg: String = arg.get()?;
+        cmd_args.push(arg);
+    }
+
+    let output = match Command::new(program).args(cmd_args).output() {
+        Ok(output) => output,
+        Err(e) => {
+            let kind = Symbol::new(format!("{:?}", e.kind()));
+            return Ok(fail(vec![kind.into()]));
+        }
+    };
+
+    let code = output
+        .status
+        .code()
+        .map_or_else(nil, |code| f64::from(code).into());
+    let tup = Tuple::from(vec![
+        String::from_utf8_lossy(&output.stdout).into_owned().into(),
+        String::from_utf8_lossy(&output.stderr).into_owned().into(),
+        code,
+    ]);
+
+    Ok(ok(vec![Value::Tuple(tup)]))
+}
+
+#[cfg(unix)]
+#[test]
+fn system_captures_output() {
+    let args: List = std::iter::once("hello".to_string().into()).collect();
+    let output = system(&["echo".to_string().into(), args.into()]).unwrap();
+
+    let expected = Tuple::from(vec![
+        "hello\n".to_string().into(),
+        String::new().into(),
+        Value::Num(0.0),
+    ]);
+    assert_eq!(output, ok(vec![Value::Tuple(expected)]));
+
+    let missing = system(&["yex-no-such-command".to_string().into(), List::new().into()]);
+    assert_eq!(
+        missing.unwrap(),
+        fail(vec![Symbol::from("NotFound").into()])
+    );
+}
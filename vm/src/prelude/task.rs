@@ -0,0 +1,104 @@
+//! Cooperative tasks
+//!
+//! A task is a function called with `()`. It either finishes by returning any value, or suspends
+//! itself by returning `yield k` or `Channel.recv ch k`, where `k` is the function that continues
+//! the task the next time the scheduler gets to it. `runTasks` runs the spawned tasks round-robin
+//! until all of them are finished.
+//!
+//! Suspension is continuation passing, the VM doesn't save a task's frames. `yield` doesn't stop
+//! the function calling it, it only builds the value that asks the scheduler for a turn, so it
+//! has to be what the task returns, and whatever the task still has to do goes in `k`. The
+//! pending continuations are kept in the `VirtualMachine`'s task queue.
+use crate::{
+    error::InterpretResult,
+    gc::GcRef,
+    literal::{channel::methods::channel_of, nil, Value},
+    raise_err, List, Symbol, Tuple, VirtualMachine, YexModule,
+};
+
+pub(crate) const YIELD: &str = "Task.yield";
+pub(crate) const RECV: &str = "Task.recv";
+
+/// A task waiting on the scheduler queue
+pub(crate) enum Task {
+    /// Calls the function with the argument
+    Run(Value, Value),
+    /// Calls the function with the next value of the channel, once there is one
+    Recv(usize, Value),
+}
+
+/// Creates the value a task returns to give control back to the scheduler
+pub(crate) fn suspend(tag: &str, args: Vec<Value>) -> Value {
+    let this = GcRef::new(YexModule::default());
+    Value::Tagged(this, Symbol::from(tag), Tuple::from(args))
+}
+
+pub fn spawn(vm: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
+    vm.tasks
+        .push_back(Task::Run(args[0].clone(), Tuple::from(vec![]).into()));
+    Ok(nil())
+}
+
+/// Suspends the task, `k` is queued and called with `()` on the task's next turn. Only works as
+/// the value the task returns, see the module docs
+pub fn r#yield(_: &mut VirtualMachine, args: &[Value]) -> InterpretResult<Value> {
+    Ok(suspend(YIELD, vec![args[0].clone()]))
+}
+
+/// Runs the spawned tasks until the queue is empty, returning what each task finished with, in
+/// the order they finished
+pub fn run_tasks(vm: &mut VirtualMachine, _: &[Value]) -> InterpretResult<Value> {
+    let result = schedule(vm);
+    if result.is_err() {
+        vm.tasks.clear();
+    }
+
+    result
+}
+
+fn schedule(vm: &mut VirtualMachine) -> InterpretResult<Value> {
+    let mut finished = vec![];
+    // how many tasks in a row were waiting on an empty channel
+    let mut waiting = 0;
+
+    while let Some(task) = vm.tasks.pop_front() {
+        let (fun, arg) = match task {
+            Task::Run(fun, arg) => (fun, arg),
+            Task::Recv(id, k) => {
+                if let Some(value) = vm.channels[id].pop_front() {
+                    (k, value)
+                } else {
+                    vm.tasks.push_back(Task::Recv(id, k));
+                    waiting += 1;
+
+                    if waiting >= vm.tasks.len() {
+                        return Err(raise_err!(
+                            DeadlockError,
+                            "Every task is waiting on an empty channel"
+                        ));
+                    }
+                    continue;
+                }
+            }
+        };
+        waiting = 0;
+
+        vm.push(arg)?;
+        vm.push(fun)?;
+        vm.call(1)?;
+
+        match vm.pop() {
+            Value::Tagged(_, tag, args) if tag == Symbol::from(YIELD) => {
+                vm.tasks
+                    .push_back(Task::Run(args.0[0].clone(), Tuple::from(vec![]).into()));
+            }
+            Value::Tagged(_, tag, args) if tag == Symbol::from(RECV) => {
+                let id = channel_of(&args.0[0])?;
+                vm.tasks.push_back(Task::Recv(id, args.0[1].clone()));
+            }
+            value => finished.push(value),
+        }
+    }
+
+    Ok(finished.into_iter().rev().collect::<List>().into())
+}
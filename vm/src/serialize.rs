@@ -0,0 +1,487 @@
+use crate::{
+    error::InterpretResult, gc::GcRef, raise, Bytecode, EnvTable, Fn, FnKind, List, OpCode,
+    OpCodeMetadata, Struct, Symbol, Tuple, Value, YexModule,
+};
+
+const MAGIC: &[u8; 4] = b"YEXB";
+const VERSION: u8 = 1;
+
+struct Writer {
+    out: Vec<u8>,
+    /// the modules being written, the variants of a type are tagged with the type itself
+    modules: Vec<*const YexModule>,
+}
+
+impl Writer {
+    fn byte(&mut self, byte: u8) {
+        self.out.push(byte);
+    }
+
+    fn usize(&mut self, n: usize) {
+        self.out.extend_from_slice(&(n as u64).to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.usize(bytes.len());
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn sym(&mut self, sym: Symbol) {
+        self.bytes(sym.as_str().as_bytes());
+    }
+
+    fn op(&mut self, op: OpCode) {
+        let (tag, arg) = match op {
+            OpCode::Nop => (0, None),
+            OpCode::Push(n) => (1, Some(n)),
+            OpCode::Pop => (2, None),
+            OpCode::Dup => (3, None),
+            OpCode::Over => (4, None),
+            OpCode::Load(n) => (5, Some(n)),
+            OpCode::Save(n) => (6, Some(n)),
+            OpCode::Jmf(n) => (7, Some(n)),
+            OpCode::Jmp(n) => (8, Some(n)),
+            OpCode::Call(n) => (9, Some(n)),
+            OpCode::TCall(n) => (10, Some(n)),
+            OpCode::Prep => (11, None),
+            OpCode::Rev => (12, None),
+            OpCode::RevN(n) => (13, Some(n)),
+            OpCode::Add => (14, None),
+            OpCode::Rem => (15, None),
+            OpCode::Sub => (16, None),
+            OpCode::Mul => (17, None),
+            OpCode::Div => (18, None),
+            OpCode::Neg => (19, None),
+            OpCode::Len => (20, None),
+            OpCode::Not => (21, None),
+            OpCode::Xor => (22, None),
+            OpCode::Shr => (23, None),
+            OpCode::Shl => (24, None),
+            OpCode::BitAnd => (25, None),
+            OpCode::BitOr => (26, None),
+            OpCode::Eq => (27, None),
+            OpCode::Less => (28, None),
+            OpCode::LessEq => (29, None),
+            OpCode::Type => (30, None),
+            OpCode::Try(n) => (31, Some(n)),
+            OpCode::EndTry => (32, None),
+            OpCode::Tup(n) => (33, Some(n)),
+            OpCode::TupGet(n) => (34, Some(n)),
+            OpCode::Struct(n) => (35, Some(n)),
+            OpCode::TagOf => (36, None),
+            OpCode::TagTup => (37, None),
+            // the opcodes that carry a symbol
+            OpCode::Loag(sym) => return self.sym_op(64, sym),
+            OpCode::Savg(sym) => return self.sym_op(65, sym),
+            OpCode::Redefg(sym) => return self.sym_op(66, sym),
+            OpCode::Ref(sym) => return self.sym_op(67, sym),
+            OpCode::Get(sym) => return self.sym_op(68, sym),
+            OpCode::Has(sym) => return self.sym_op(69, sym),
+            OpCode::Tag(sym) => return self.sym_op(70, sym),
+            OpCode::Set(sym) => return self.sym_op(71, sym),
+        };
+
+        self.byte(tag);
+        if let Some(arg) = arg {
+            self.usize(arg);
+        }
+    }
+
+    fn sym_op(&mut self, tag: u8, sym: Symbol) {
+        self.byte(tag);
+        self.sym(sym);
+    }
+
+    fn bytecode(&mut self, bytecode: &Bytecode) {
+        self.usize(bytecode.len());
+        for op in bytecode {
+            self.usize(op.line);
+            self.usize(op.column);
+            self.op(op.opcode);
+        }
+    }
+
+    fn values(&mut self, values: impl ExactSizeIterator<Item = Value>) -> InterpretResult<()> {
+        self.usize(values.len());
+        for value in values {
+            self.value(&value)?;
+        }
+        Ok(())
+    }
+
+    fn module(&mut self, module: &YexModule) -> InterpretResult<()> {
+        let fields: Vec<_> = module.fields.iter().collect();
+
+        self.byte(10);
+        self.sym(module.name);
+        self.usize(fields.len());
+
+        self.modules.push(std::ptr::from_ref(module));
+        for (name, value) in fields {
+            self.sym(name);
+            self.value(&value)?;
+        }
+        self.modules.pop();
+
+        Ok(())
+    }
+
+    fn value(&mut self, value: &Value) -> InterpretResult<()> {
+        match value {
+            Value::Nil => self.byte(0),
+            Value::Bool(b) => {
+                self.byte(1);
+                self.byte(u8::from(*b));
+            }
+            Value::Num(n) => {
+                self.byte(2);
+                self.out.extend_from_slice(&n.to_le_bytes());
+            }
+            Value::Str(s) => {
+                self.byte(3);
+                self.bytes(s.as_bytes());
+            }
+            Value::Bytes(b) => {
+                self.byte(4);
+                self.bytes(b);
+            }
+            Value::Sym(s) => {
+                self.byte(5);
+                self.sym(s.0);
+            }
+            Value::List(xs) => {
+                self.byte(6);
+                self.values(xs.iter().collect::<Vec<_>>().into_iter())?;
+            }
+            Value::Tuple(xs) => {
+                self.byte(7);
+                self.values(xs.0.iter().cloned())?;
+            }
+            Value::Struct(fields) => {
+                self.byte(8);
+                self.usize(fields.len());
+                for (name, value) in fields.0.iter() {
+                    self.sym(*name);
+                    self.value(value)?;
+                }
+            }
+            Value::Fn(f) => match &*f.body {
+                FnKind::Bytecode(bytecode) => {
+                    self.byte(9);
+                    self.usize(f.arity);
+                    self.bytecode(bytecode);
+                    self.values(f.args.iter().cloned().collect::<Vec<_>>().into_iter())?;
+                }
+                FnKind::Native(_) => {
+                    raise!(SerializeError, "Native functions can't be serialized")?;
+                }
+            },
+            Value::Module(module) => self.module(module)?,
+            Value::Tagged(module, tag, tup) => {
+                let ptr = std::ptr::from_ref::<YexModule>(module);
+                if let Some(depth) = self.modules.iter().rev().position(|it| *it == ptr) {
+                    self.byte(11);
+                    self.usize(depth);
+                } else {
+                    self.byte(12);
+                    self.module(module)?;
+                }
+                self.sym(*tag);
+                self.values(tup.0.iter().cloned())?;
+            }
+            other => raise!(
+                SerializeError,
+                "Values of type '{}' can't be serialized",
+                other.type_name()
+            )?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes a compiled program, so it can be loaded back with [`deserialize_program`] without
+/// compiling it again
+///
+/// # Errors
+/// Raises a `SerializeError` if a constant can't be serialized, like native functions or FFI handles
+pub fn serialize_program(bytecode: &Bytecode, constants: &[Value]) -> InterpretResult<Vec<u8>> {
+    let mut writer = Writer {
+        out: vec![],
+        modules: vec![],
+    };
+    writer.out.extend_from_slice(MAGIC);
+    writer.byte(VERSION);
+    writer.bytecode(bytecode);
+    writer.values(constants.iter().cloned())?;
+
+    Ok(writer.out)
+}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    modules: Vec<GcRef<YexModule>>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> InterpretResult<&'a [u8]> {
+        if len > self.input.len() {
+            return raise!(BytecodeError, "Unexpected end of the serialized program");
+        }
+
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> InterpretResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn usize(&mut self) -> InterpretResult<usize> {
+        let bytes = self.take(8)?.try_into().unwrap();
+        match usize::try_from(u64::from_le_bytes(bytes)) {
+            Ok(n) => Ok(n),
+            Err(_) => raise!(
+                BytecodeError,
+                "Integer out of range in the serialized program"
+            ),
+        }
+    }
+
+    fn bytes(&mut self) -> InterpretResult<&'a [u8]> {
+        let len = self.usize()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> InterpretResult<String> {
+        match String::from_utf8(self.bytes()?.to_vec()) {
+            Ok(s) => Ok(s),
+            Err(_) => raise!(BytecodeError, "Invalid UTF-8 in the serialized program"),
+        }
+    }
+
+    fn sym(&mut self) -> InterpretResult<Symbol> {
+        Ok(Symbol::new(self.string()?))
+    }
+
+    fn op(&mut self) -> InterpretResult<OpCode> {
+        let op = match self.byte()? {
+            0 => OpCode::Nop,
+            1 => OpCode::Push(self.usize()?),
+            2 => OpCode::Pop,
+            3 => OpCode::Dup,
+            4 => OpCode::Over,
+            5 => OpCode::Load(self.usize()?),
+            6 => OpCode::Save(self.usize()?),
+            7 => OpCode::Jmf(self.usize()?),
+            8 => OpCode::Jmp(self.usize()?),
+            9 => OpCode::Call(self.usize()?),
+            10 => OpCode::TCall(self.usize()?),
+            11 => OpCode::Prep,
+            12 => OpCode::Rev,
+            13 => OpCode::RevN(self.usize()?),
+            14 => OpCode::Add,
+            15 => OpCode::Rem,
+            16 => OpCode::Sub,
+            17 => OpCode::Mul,
+            18 => OpCode::Div,
+            19 => OpCode::Neg,
+            20 => OpCode::Len,
+            21 => OpCode::Not,
+            22 => OpCode::Xor,
+            23 => OpCode::Shr,
+            24 => OpCode::Shl,
+            25 => OpCode::BitAnd,
+            26 => OpCode::BitOr,
+            27 => OpCode::Eq,
+            28 => OpCode::Less,
+            29 => OpCode::LessEq,
+            30 => OpCode::Type,
+            31 => OpCode::Try(self.usize()?),
+            32 => OpCode::EndTry,
+            33 => OpCode::Tup(self.usize()?),
+            34 => OpCode::TupGet(self.usize()?),
+            35 => OpCode::Struct(self.usize()?),
+            36 => OpCode::TagOf,
+            37 => OpCode::TagTup,
+            64 => OpCode::Loag(self.sym()?),
+            65 => OpCode::Savg(self.sym()?),
+            66 => OpCode::Redefg(self.sym()?),
+            67 => OpCode::Ref(self.sym()?),
+            68 => OpCode::Get(self.sym()?),
+            69 => OpCode::Has(self.sym()?),
+            70 => OpCode::Tag(self.sym()?),
+            71 => OpCode::Set(self.sym()?),
+            tag => return raise!(BytecodeError, "Unknown opcode tag {}", tag),
+        };
+
+        Ok(op)
+    }
+
+    fn bytecode(&mut self) -> InterpretResult<Bytecode> {
+        let len = self.usize()?;
+        let mut bytecode = Vec::with_capacity(len.min(self.input.len()));
+        for _ in 0..len {
+            let line = self.usize()?;
+            let column = self.usize()?;
+            bytecode.push(OpCodeMetadata::new(line, column, self.op()?));
+        }
+        Ok(bytecode)
+    }
+
+    fn values(&mut self) -> InterpretResult<Vec<Value>> {
+        let len = self.usize()?;
+        let mut values = Vec::with_capacity(len.min(self.input.len()));
+        for _ in 0..len {
+            values.push(self.value()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a module, after its tag
+    fn module(&mut self) -> InterpretResult<GcRef<YexModule>> {
+        let name = self.sym()?;
+        let len = self.usize()?;
+
+        self.modules
+            .push(GcRef::new(YexModule::new(name, EnvTable::new())));
+        for _ in 0..len {
+            let field = self.sym()?;
+            let value = self.value()?;
+
+            // SAFETY: the module is only shared with the values inside of it, which don't read it
+            // while it's being built
+            unsafe { self.modules.last_mut().unwrap().mut_ref() }
+                .fields
+                .insert(field, value);
+        }
+
+        Ok(self.modules.pop().unwrap())
+    }
+
+    fn value(&mut self) -> InterpretResult<Value> {
+        let value = match self.byte()? {
+            0 => Value::Nil,
+            1 => Value::Bool(self.byte()? != 0),
+            2 => Value::Num(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            3 => self.string()?.into(),
+            4 => Value::Bytes(GcRef::new(self.bytes()?.to_vec())),
+            5 => self.sym()?.into(),
+            6 => self.values()?.into_iter().rev().collect::<List>().into(),
+            7 => Value::Tuple(Tuple::from(self.values()?)),
+            8 => {
+                let len = self.usize()?;
+                let mut fields = Vec::with_capacity(len.min(self.input.len()));
+                for _ in 0..len {
+                    fields.push((self.sym()?, self.value()?));
+                }
+                Value::Struct(Struct::from(fields))
+            }
+            9 => {
+                let mut fun = Fn::new_bt(self.usize()?, self.bytecode()?);
+                let args = self.values()?;
+                if args.len() > fun.args.capacity() {
+                    return raise!(BytecodeError, "Too many applied arguments");
+                }
+                for arg in args {
+                    fun.args.push(arg);
+                }
+                Value::Fn(GcRef::new(fun))
+            }
+            10 => Value::Module(self.module()?),
+            11 => {
+                let depth = self.usize()?;
+                let module = match self.modules.iter().rev().nth(depth) {
+                    Some(module) => module.clone(),
+                    None => return raise!(BytecodeError, "Tagged value outside of its type"),
+                };
+                Value::Tagged(module, self.sym()?, Tuple::from(self.values()?))
+            }
+            12 => {
+                if self.byte()? != 10 {
+                    return raise!(BytecodeError, "Expected the type of a tagged value");
+                }
+                let module = self.module()?;
+                Value::Tagged(module, self.sym()?, Tuple::from(self.values()?))
+            }
+            tag => return raise!(BytecodeError, "Unknown value tag {}", tag),
+        };
+
+        Ok(value)
+    }
+}
+
+/// Loads a program serialized by [`serialize_program`]
+///
+/// # Errors
+/// Raises a `BytecodeError` if the input isn't a serialized program
+pub fn deserialize_program(input: &[u8]) -> InterpretResult<(Bytecode, Vec<Value>)> {
+    let mut reader = Reader {
+        input,
+        modules: vec![],
+    };
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return raise!(BytecodeError, "Not a serialized yex program");
+    }
+
+    let version = reader.byte()?;
+    if version != VERSION {
+        return raise!(BytecodeError, "Unsupported program version {}", version);
+    }
+
+    let bytecode = reader.bytecode()?;
+    let constants = reader.values()?;
+
+    if !reader.input.is_empty() {
+        return raise!(BytecodeError, "Trailing data after the serialized program");
+    }
+
+    Ok((bytecode, constants))
+}
+
+#[test]
+fn values_round_trip() {
+    let body = vec![
+        OpCodeMetadata::new(1, 2, OpCode::Load(0)),
+        OpCodeMetadata::new(1, 4, OpCode::Get(Symbol::from("x"))),
+    ];
+    let constants = vec![
+        Value::Nil,
+        Value::Num(-1.5),
+        Value::Sym(Symbol::from("ok").into()),
+        vec![Value::Bool(true), "str".to_string().into()]
+            .into_iter()
+            .rev()
+            .collect::<List>()
+            .into(),
+        Value::Struct(Struct::from(vec![(Symbol::from("x"), Value::Num(1.0))])),
+        Value::Fn(GcRef::new(Fn::new_bt(1, body))),
+    ];
+    let bytecode = vec![OpCodeMetadata::new(3, 1, OpCode::Push(5))];
+
+    let bytes = serialize_program(&bytecode, &constants).unwrap();
+    let (bt, ct) = deserialize_program(&bytes).unwrap();
+
+    assert_eq!(bt, bytecode);
+    assert_eq!(bt[0].line, 3);
+    assert_eq!(ct, constants);
+}
+
+#[test]
+fn native_functions_are_not_serialized() {
+    let native = Fn::new_native(1, |_, _| Ok(Value::Nil));
+    let err = serialize_program(&vec![], &[Value::Fn(GcRef::new(native))]).unwrap_err();
+    assert_eq!(err.err.as_str(), "SerializeError");
+}
+
+#[test]
+fn malformed_programs_are_rejected() {
+    let bytes = serialize_program(&vec![OpCodeMetadata::new(1, 1, OpCode::Pop)], &[]).unwrap();
+
+    for input in [&bytes[..bytes.len() - 1], b"YEXC\x01", &[]] {
+        let err = deserialize_program(input).unwrap_err();
+        assert_eq!(err.err.as_str(), "BytecodeError");
+    }
+}
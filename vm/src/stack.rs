@@ -42,6 +42,12 @@ impl<T, const S: usize> StackVec<T, S> {
         self.len
     }
 
+    /// Returns how many elements the `StackVec` can hold
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        S
+    }
+
     /// checks if the stack is empty
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -0,0 +1,168 @@
+use crate::{
+    error::{InterpretError, InterpretResult},
+    Bytecode, FnKind, OpCode, OpCodeMetadata, Symbol, Value, YexModule, MAX_LOCALS,
+};
+
+fn invalid(op: &OpCodeMetadata, msg: String) -> InterpretResult<()> {
+    Err(InterpretError {
+        msg,
+        err: Symbol::from("BytecodeError"),
+        payload: None,
+        line: op.line,
+        column: op.column,
+    })
+}
+
+fn validate_ops(bytecode: &Bytecode, constants: &[Value]) -> InterpretResult<()> {
+    let len = bytecode.len();
+
+    for (ip, op) in bytecode.iter().enumerate() {
+        match op.opcode {
+            OpCode::Push(idx) if idx >= constants.len() => {
+                invalid(op, format!("Constant {idx} out of range at {ip}"))?;
+            }
+            // jumping to the end of the bytecode just stops it
+            OpCode::Jmp(offset) | OpCode::Jmf(offset) if offset > len => {
+                invalid(op, format!("Jump to {offset} out of range at {ip}"))?;
+            }
+            // the vm moves to the instruction after the rescue address
+            OpCode::Try(offset) if offset >= len => {
+                invalid(op, format!("Rescue address {offset} out of range at {ip}"))?;
+            }
+            OpCode::Load(slot) | OpCode::Save(slot) if slot >= MAX_LOCALS => {
+                invalid(op, format!("Local {slot} out of range at {ip}"))?;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the functions in a constant, including the ones nested in modules, tagged values,
+/// tuples, lists and partially applied arguments
+fn validate_constant(
+    value: &Value,
+    constants: &[Value],
+    seen: &mut Vec<*const YexModule>,
+) -> InterpretResult<()> {
+    match value {
+        Value::Fn(fun) => {
+            if let FnKind::Bytecode(body) = &*fun.body {
+                validate_ops(body, constants)?;
+            }
+            fun.args
+                .iter()
+                .try_for_each(|arg| validate_constant(arg, constants, seen))
+        }
+        Value::Module(module) => validate_module(module, constants, seen),
+        Value::Tagged(module, _, tup) => {
+            validate_module(module, constants, seen)?;
+            validate_constant(&Value::Tuple(tup.clone()), constants, seen)
+        }
+        Value::Tuple(tup) => tup
+            .0
+            .iter()
+            .try_for_each(|elem| validate_constant(elem, constants, seen)),
+        Value::List(xs) => xs
+            .iter()
+            .try_for_each(|elem| validate_constant(&elem, constants, seen)),
+        _ => Ok(()),
+    }
+}
+
+/// A type's constructors are tagged with the type's own module, so modules already seen are
+/// skipped
+fn validate_module(
+    module: &YexModule,
+    constants: &[Value],
+    seen: &mut Vec<*const YexModule>,
+) -> InterpretResult<()> {
+    let ptr: *const YexModule = module;
+    if seen.contains(&ptr) {
+        return Ok(());
+    }
+    seen.push(ptr);
+
+    module
+        .fields
+        .iter()
+        .try_for_each(|(_, field)| validate_constant(&field, constants, seen))
+}
+
+/// Checks that every constant index, jump address and local slot used by the bytecode, and by the
+/// functions in the constant table, is in range, so running it can't index out of bounds
+pub fn validate(bytecode: &Bytecode, constants: &[Value]) -> InterpretResult<()> {
+    validate_ops(bytecode, constants)?;
+
+    let mut seen = vec![];
+    constants
+        .iter()
+        .try_for_each(|constant| validate_constant(constant, constants, &mut seen))
+}
+
+#[cfg(test)]
+fn bytecode(ops: &[OpCode]) -> Bytecode {
+    ops.iter()
+        .map(|op| OpCodeMetadata::new(1, 1, *op))
+        .collect()
+}
+
+#[test]
+fn valid_bytecode() {
+    let ops = bytecode(&[OpCode::Push(0), OpCode::Jmf(3), OpCode::Jmp(0)]);
+    assert!(validate(&ops, &[Value::Bool(true)]).is_ok());
+}
+
+#[test]
+fn out_of_range_operands() {
+    for ops in [
+        [OpCode::Push(1), OpCode::Nop],
+        [OpCode::Jmp(3), OpCode::Nop],
+        [OpCode::Try(2), OpCode::Nop],
+        [OpCode::Load(MAX_LOCALS), OpCode::Nop],
+    ] {
+        let err = validate(&bytecode(&ops), &[Value::Nil]).unwrap_err();
+        assert_eq!(err.err, Symbol::from("BytecodeError"), "{ops:?}");
+    }
+}
+
+#[test]
+fn validates_function_constants() {
+    let fun = crate::Fn::new_bt(0, bytecode(&[OpCode::Push(5)]));
+    let constants = [Value::Fn(crate::gc::GcRef::new(fun))];
+
+    assert!(validate(&bytecode(&[OpCode::Push(0)]), &constants).is_err());
+}
+
+#[test]
+fn validates_nested_functions() {
+    use crate::{gc::GcRef, EnvTable, Fn, Tuple};
+
+    for op in [OpCode::Push(5), OpCode::Jmp(5)] {
+        let fun = || Value::Fn(GcRef::new(Fn::new_bt(0, bytecode(&[op]))));
+
+        let mut fields = EnvTable::new();
+        fields.insert(Symbol::from("method"), fun());
+        let module = GcRef::new(YexModule {
+            name: Symbol::from("Mod"),
+            fields,
+        });
+
+        let mut partial = Fn::new_bt(1, bytecode(&[OpCode::Nop]));
+        partial.args.push(fun());
+
+        for constant in [
+            Value::Module(module),
+            Value::Tagged(
+                GcRef::new(YexModule::default()),
+                Symbol::from("tag"),
+                Tuple::from(vec![fun()]),
+            ),
+            Value::Fn(GcRef::new(partial)),
+        ] {
+            let err = validate(&bytecode(&[OpCode::Nop]), &[constant]).unwrap_err();
+            assert_eq!(err.err, Symbol::from("BytecodeError"), "{op:?}");
+        }
+    }
+}
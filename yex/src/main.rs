@@ -3,26 +3,76 @@ use rustyline::Editor;
 use std::{
     env::args,
     fs::{self, File},
+    path::Path,
     process::exit,
 };
-use vm::{OpCode, OpCodeMetadata, VirtualMachine};
+use vm::{Bytecode, OpCode, OpCodeMetadata, Value, VirtualMachine};
 
-fn eval_file(file: &str) {
-    let file = if let Ok(file) = fs::read_to_string(file) {
-        file
+/// Extension of the files written by `yex --compile`
+const COMPILED_EXT: &str = "yexc";
+
+fn compile(file: &str) -> (Bytecode, Vec<Value>) {
+    let source = if let Ok(source) = fs::read_to_string(file) {
+        source
     } else {
         eprintln!("error reading {}", file);
         exit(1);
     };
 
-    let (bt, ct) = match front::parse(file) {
+    match front::parse(source) {
         Ok(res) => res,
         Err(e) => {
             eprintln!("{}", e);
             exit(1);
         }
+    }
+}
+
+fn load_compiled(file: &str) -> (Bytecode, Vec<Value>) {
+    let Ok(bytes) = fs::read(file) else {
+        eprintln!("error reading {file}");
+        exit(1);
     };
 
+    match vm::deserialize_program(&bytes) {
+        Ok(res) => res,
+        Err(e) => {
+            eprintln!("{e}");
+            exit(1);
+        }
+    }
+}
+
+fn compile_file(file: &str) {
+    let (bt, ct) = compile(file);
+
+    let bytes = match vm::serialize_program(&bt, &ct) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("{e}");
+            exit(1);
+        }
+    };
+
+    let out = Path::new(file).with_extension(COMPILED_EXT);
+    if fs::write(&out, bytes).is_err() {
+        eprintln!("error writing {}", out.display());
+        exit(1);
+    }
+}
+
+fn eval_file(file: &str) {
+    let (bt, ct) = if Path::new(file).extension() == Some(COMPILED_EXT.as_ref()) {
+        load_compiled(file)
+    } else {
+        compile(file)
+    };
+
+    if let Err(e) = vm::validate(&bt, &ct) {
+        eprintln!("{e}");
+        exit(1);
+    }
+
     let mut vm = VirtualMachine::default();
 
     vm.set_consts(ct);
@@ -50,8 +100,11 @@ fn start(args: impl Iterator<Item = String>) -> i32 {
     }
 
     if args.size_hint().0 > 1 {
-        for args in args.skip(1) {
-            eval_file(&args);
+        let mut args = args.skip(1).peekable();
+        if args.next_if(|arg| arg == "--compile").is_some() {
+            args.for_each(|file| compile_file(&file));
+        } else {
+            args.for_each(|file| eval_file(&file));
         }
         return 0;
     }
@@ -73,7 +126,7 @@ fn start(args: impl Iterator<Item = String>) -> i32 {
         repl.add_history_entry(&line);
 
         if line.starts_with("def") || line.starts_with("let") || line.starts_with("type") {
-            match front::parse(line) {
+            match front::parse_permissive(line) {
                 Ok((mut bt, ct)) => {
                     patch_bytecode(&mut bt, vm.constants.len());
                     vm.constants.extend(ct);
@@ -103,7 +156,6 @@ fn start(args: impl Iterator<Item = String>) -> i32 {
 
 #[test]
 fn test_yex_file() {
-    use std::path::Path;
     if Path::new("../tests.yex").exists() {
         let args = vec![String::new(), String::from("../tests.yex")].into_iter();
         assert_eq!(start(args), 0);